@@ -0,0 +1,305 @@
+use std::time::Duration;
+
+use iced::{
+    Element, Length, Task,
+    futures::{StreamExt, stream},
+    widget::{
+        button, checkbox, column, container, row, scrollable, slider, space, table, text,
+        text_editor,
+    },
+};
+
+use crate::{Measurement, StyleConfig, measure_url};
+
+const DEFAULT_PARALLELISM: u8 = 4;
+const MAX_PARALLELISM: u8 = 16;
+
+#[derive(Debug, Clone)]
+pub enum Status {
+    Pending,
+    Running,
+    Done(Measurement),
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub url: String,
+    pub status: Status,
+}
+
+/// Minimum, median and maximum download time over all successful fetches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Summary {
+    /// Returns `None` if there are no durations to summarize.
+    pub fn from_durations(mut durations: Vec<Duration>) -> Option<Self> {
+        durations.sort();
+        let min = *durations.first()?;
+        let max = *durations.last()?;
+        let mid = durations.len() / 2;
+        let median = if durations.len().is_multiple_of(2) {
+            (durations[mid - 1] + durations[mid]) / 2
+        } else {
+            durations[mid]
+        };
+        Some(Self { min, median, max })
+    }
+}
+
+/// Measures a pasted list of URLs concurrently, a few at a time.
+pub struct Batch {
+    urls: text_editor::Content,
+    secure: bool,
+    parallelism: u8,
+    entries: Vec<Entry>,
+    next: usize,
+    /// Client of the current run, shared by all of its requests.
+    client: Option<reqwest::Client>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    UrlsEdited(text_editor::Action),
+    SecureChanged(bool),
+    ParallelismChanged(u8),
+    Start,
+    /// The client for the run is built.
+    Ready(Result<reqwest::Client, String>),
+    Finished(usize, Result<Measurement, String>),
+}
+
+pub enum Action {
+    None,
+    Run(Task<Message>),
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Self {
+            urls: text_editor::Content::new(),
+            secure: true,
+            parallelism: DEFAULT_PARALLELISM,
+            entries: Vec::new(),
+            next: 0,
+            client: None,
+        }
+    }
+}
+
+impl Batch {
+    /// Process a message; `client` is only awaited when a run starts.
+    #[must_use]
+    pub fn update(
+        &mut self,
+        message: Message,
+        client: impl Future<Output = Result<reqwest::Client, String>> + Send + 'static,
+    ) -> Action {
+        match message {
+            Message::UrlsEdited(action) => {
+                self.urls.perform(action);
+                Action::None
+            }
+            Message::SecureChanged(secure) => {
+                self.secure = secure;
+                Action::None
+            }
+            Message::ParallelismChanged(parallelism) => {
+                self.parallelism = parallelism;
+                Action::None
+            }
+            Message::Start => {
+                self.entries = parse_urls(&self.urls.text())
+                    .into_iter()
+                    .map(|url| Entry {
+                        url,
+                        status: Status::Pending,
+                    })
+                    .collect();
+                self.next = 0;
+                self.client = None;
+                Action::Run(Task::perform(client, Message::Ready))
+            }
+            Message::Ready(Err(error)) => {
+                for entry in &mut self.entries {
+                    entry.status = Status::Failed(error.clone());
+                }
+                self.next = self.entries.len();
+                Action::None
+            }
+            Message::Ready(Ok(client)) => {
+                self.client = Some(client);
+                let tasks: Vec<_> = (0..self.parallelism)
+                    .filter_map(|_| self.start_next())
+                    .collect();
+                Action::Run(Task::batch(tasks))
+            }
+            Message::Finished(index, result) => {
+                if let Some(entry) = self.entries.get_mut(index) {
+                    entry.status = match result {
+                        Ok(measurement) => Status::Done(measurement),
+                        Err(error) => Status::Failed(error),
+                    };
+                }
+                match self.start_next() {
+                    Some(task) => Action::Run(task),
+                    None => Action::None,
+                }
+            }
+        }
+    }
+
+    /// Mark the next pending entry as running and return the task fetching it.
+    fn start_next(&mut self) -> Option<Task<Message>> {
+        let index = self.next;
+        let client = self.client.clone()?;
+        let entry = self.entries.get_mut(index)?;
+        self.next += 1;
+
+        entry.status = Status::Running;
+        let url = entry.url.clone();
+        Some(Task::perform(
+            measure_url(client, url, self.secure),
+            move |result| Message::Finished(index, result),
+        ))
+    }
+
+    fn is_running(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|e| matches!(e.status, Status::Pending | Status::Running))
+    }
+
+    pub fn summary(&self) -> Option<Summary> {
+        Summary::from_durations(
+            self.entries
+                .iter()
+                .filter_map(|e| match e.status {
+                    Status::Done(m) => Some(m.elapsed),
+                    _ => None,
+                })
+                .collect(),
+        )
+    }
+
+    pub fn view<'a>(&'a self, style: &StyleConfig) -> Element<'a, Message> {
+        let mono = style.mono_font;
+        let subtitle = style.subtitle_color;
+
+        let run_button = if self.is_running() {
+            button("Running...")
+        } else {
+            button("Run").on_press(Message::Start)
+        };
+
+        let controls = row![
+            checkbox(self.secure)
+                .label("HTTPS")
+                .on_toggle(Message::SecureChanged),
+            text(format!("Parallelism: {}", self.parallelism)),
+            slider(
+                1..=MAX_PARALLELISM,
+                self.parallelism,
+                Message::ParallelismChanged
+            )
+            .width(200),
+            run_button,
+        ]
+        .spacing(12)
+        .align_y(iced::Alignment::Center);
+
+        let columns = [
+            table::column(text("URL").font(mono).color(subtitle), |entry: &Entry| {
+                text(&entry.url).font(mono)
+            }),
+            table::column(
+                text("Status").font(mono).color(subtitle),
+                |entry: &Entry| {
+                    text(match &entry.status {
+                        Status::Pending => "pending".to_string(),
+                        Status::Running => "running".to_string(),
+                        Status::Done(_) => "done".to_string(),
+                        Status::Failed(error) => error.clone(),
+                    })
+                },
+            ),
+            table::column(text("Time").font(mono).color(subtitle), |entry: &Entry| {
+                text(match entry.status {
+                    Status::Done(m) => format!("{:.2?}", m.elapsed),
+                    _ => String::new(),
+                })
+                .font(mono)
+            }),
+            table::column(text("Size").font(mono).color(subtitle), |entry: &Entry| {
+                text(match entry.status {
+                    Status::Done(m) => humansize::format_size(m.size, humansize::DECIMAL),
+                    _ => String::new(),
+                })
+                .font(mono)
+            }),
+        ];
+
+        let summary = match self.summary() {
+            Some(s) => format!(
+                "min {:.2?} · median {:.2?} · max {:.2?}",
+                s.min, s.median, s.max
+            ),
+            None => "No results yet".to_string(),
+        };
+
+        column![
+            text_editor(&self.urls)
+                .placeholder("One URL per line (e.g. example.com)")
+                .on_action(Message::UrlsEdited)
+                .font(mono)
+                .height(120),
+            controls,
+            space().height(12),
+            container(scrollable(table(columns, &self.entries)).height(Length::Fill))
+                .width(iced::Fill)
+                .padding(12)
+                .style(container::rounded_box),
+            text(summary).size(style.text_size).font(mono),
+        ]
+        .spacing(12)
+        .width(iced::Fill)
+        .into()
+    }
+}
+
+/// Split pasted text into URLs, one per non-blank line.
+fn parse_urls(input: &str) -> Vec<String> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Measure all URLs without a UI, keeping at most `parallelism` requests in flight.
+///
+/// Results are returned in input order.
+pub async fn run_headless(
+    client: reqwest::Client,
+    urls: Vec<String>,
+    secure: bool,
+    parallelism: usize,
+) -> Vec<(String, Result<Measurement, String>)> {
+    stream::iter(urls)
+        .map(|url| {
+            let client = client.clone();
+            async move {
+                let result = measure_url(client, url.clone(), secure).await;
+                (url, result)
+            }
+        })
+        .buffered(parallelism.max(1))
+        .collect()
+        .await
+}
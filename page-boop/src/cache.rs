@@ -0,0 +1,323 @@
+//! On-disk HTTP cache for [`fetch_url`](crate::fetch_url).
+//!
+//! Honors `Cache-Control` (`max-age`, `no-cache`, `no-store`) and revalidates
+//! stale entries with `If-None-Match` / `If-Modified-Since`. Entries are keyed
+//! by the URL and the request headers, so responses that `Vary` on a header
+//! are never served for a request with a different value.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{self, HeaderMap};
+use serde::{Deserialize, Serialize};
+
+use crate::request::RequestOptions;
+
+/// How a response was served with respect to the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// Fresh entry, no request made.
+    Hit,
+    /// Stale entry confirmed by a `304 Not Modified`.
+    Revalidated,
+    /// Downloaded from the server.
+    Miss,
+}
+
+impl fmt::Display for CacheStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheStatus::Hit => write!(f, "cache hit"),
+            CacheStatus::Revalidated => write!(f, "revalidated"),
+            CacheStatus::Miss => write!(f, "cache miss"),
+        }
+    }
+}
+
+/// What identifies a cached response: the URL and the headers sent for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Key {
+    url: String,
+    /// Lowercase names with their values, sorted.
+    headers: Vec<(String, String)>,
+}
+
+impl Key {
+    pub fn new(url: &str, options: &RequestOptions) -> Self {
+        let mut headers: Vec<_> = options
+            .headers()
+            .map(|h| (h.name.trim().to_ascii_lowercase(), h.value.clone()))
+            .collect();
+        headers.sort();
+        Self {
+            url: url.to_string(),
+            headers,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Metadata {
+    key: Key,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    max_age: Option<u64>,
+    no_cache: bool,
+    /// Seconds since the Unix epoch.
+    stored_at: u64,
+}
+
+/// A cached response body and what is needed to judge its freshness.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    meta: Metadata,
+    pub body: Vec<u8>,
+}
+
+impl Entry {
+    /// Build an entry from a `200 OK` response, unless it may not be stored
+    /// or could never be reused.
+    pub fn from_response(key: Key, headers: &HeaderMap, body: Vec<u8>) -> Option<Entry> {
+        let mut entry = Entry {
+            meta: Metadata {
+                key,
+                etag: None,
+                last_modified: None,
+                max_age: None,
+                no_cache: false,
+                stored_at: 0,
+            },
+            body,
+        };
+        if !entry.refresh(headers) {
+            return None;
+        }
+
+        let meta = &entry.meta;
+        let reusable =
+            meta.max_age.is_some() || meta.etag.is_some() || meta.last_modified.is_some();
+        reusable.then_some(entry)
+    }
+
+    /// Update freshness information from new response headers.
+    ///
+    /// Returns `false` if the response forbids storing, or varies on
+    /// something other than request headers (`Vary: *`).
+    pub fn refresh(&mut self, headers: &HeaderMap) -> bool {
+        let directives = CacheControl::parse(headers);
+        let varies_on_anything = headers
+            .get_all(header::VARY)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|name| name.trim() == "*");
+
+        let text = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        if let Some(etag) = text(header::ETAG) {
+            self.meta.etag = Some(etag);
+        }
+        if let Some(last_modified) = text(header::LAST_MODIFIED) {
+            self.meta.last_modified = Some(last_modified);
+        }
+
+        // A 304 updates only the headers it carries.
+        if headers.contains_key(header::CACHE_CONTROL) {
+            self.meta.max_age = directives.max_age;
+            self.meta.no_cache = directives.no_cache;
+        }
+        self.meta.stored_at = now();
+
+        !directives.no_store && !varies_on_anything
+    }
+
+    /// Whether the entry may be served without asking the server.
+    pub fn is_fresh(&self) -> bool {
+        match self.meta.max_age {
+            Some(max_age) if !self.meta.no_cache => {
+                now().saturating_sub(self.meta.stored_at) < max_age
+            }
+            _ => false,
+        }
+    }
+
+    /// Add validators so the server can answer `304 Not Modified`.
+    pub fn conditional(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(etag) = &self.meta.etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.meta.last_modified {
+            request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+#[derive(Default)]
+struct CacheControl {
+    max_age: Option<u64>,
+    no_cache: bool,
+    no_store: bool,
+}
+
+impl CacheControl {
+    fn parse(headers: &HeaderMap) -> Self {
+        let mut directives = CacheControl::default();
+        let values = headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|v| v.to_str().ok());
+
+        for directive in values.flat_map(|v| v.split(',')) {
+            let directive = directive.trim().to_ascii_lowercase();
+            match directive.split_once('=') {
+                Some(("max-age", seconds)) => {
+                    directives.max_age = seconds.trim_matches('"').parse().ok();
+                }
+                _ if directive == "no-cache" => directives.no_cache = true,
+                _ if directive == "no-store" => directives.no_store = true,
+                _ => {}
+            }
+        }
+        directives
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Cache location, overridable with `PAGE_BOOP_CACHE_DIR`.
+fn dir() -> PathBuf {
+    std::env::var_os("PAGE_BOOP_CACHE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("page-boop-cache"))
+}
+
+fn paths(key: &Key) -> (PathBuf, PathBuf) {
+    let name = format!(
+        "{:016x}",
+        fnv1a(ron::to_string(key).unwrap_or_default().as_bytes())
+    );
+
+    let dir = dir();
+    (
+        dir.join(format!("{name}.ron")),
+        dir.join(format!("{name}.body")),
+    )
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher` it names files the same way in
+/// every build.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+pub async fn lookup(key: &Key) -> Option<Entry> {
+    let (meta_path, body_path) = paths(key);
+    let meta: Metadata = ron::from_str(&tokio::fs::read_to_string(meta_path).await.ok()?).ok()?;
+    // Guard against hash collisions.
+    if meta.key != *key {
+        return None;
+    }
+    let body = tokio::fs::read(body_path).await.ok()?;
+    Some(Entry { meta, body })
+}
+
+/// Store an entry. Failures are ignored; the cache is only an optimization.
+pub async fn store(entry: &Entry) {
+    let (meta_path, body_path) = paths(&entry.meta.key);
+    let Ok(meta) = ron::to_string(&entry.meta) else {
+        return;
+    };
+    let _ = tokio::fs::create_dir_all(dir()).await;
+    let _ = tokio::fs::write(body_path, &entry.body).await;
+    let _ = tokio::fs::write(meta_path, meta).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.clone(), value.parse().unwrap()))
+            .collect()
+    }
+
+    fn entry(pairs: &[(header::HeaderName, &str)]) -> Option<Entry> {
+        let key = Key::new("https://example.com/", &RequestOptions::default());
+        Entry::from_response(key, &headers(pairs), b"body".to_vec())
+    }
+
+    #[test]
+    fn cache_control_is_parsed() {
+        let directives = CacheControl::parse(&headers(&[
+            (header::CACHE_CONTROL, "public, Max-Age=\"60\""),
+            (header::CACHE_CONTROL, "no-cache"),
+        ]));
+        assert_eq!(directives.max_age, Some(60));
+        assert!(directives.no_cache);
+        assert!(!directives.no_store);
+
+        let directives = CacheControl::parse(&headers(&[(header::CACHE_CONTROL, "no-store")]));
+        assert_eq!(directives.max_age, None);
+        assert!(directives.no_store);
+    }
+
+    #[test]
+    fn freshness_follows_max_age_and_no_cache() {
+        let fresh = entry(&[(header::CACHE_CONTROL, "max-age=60")]).unwrap();
+        assert!(fresh.is_fresh());
+
+        let mut stale = fresh.clone();
+        stale.meta.stored_at -= 60;
+        assert!(!stale.is_fresh());
+
+        let no_cache = entry(&[(header::CACHE_CONTROL, "max-age=60, no-cache")]).unwrap();
+        assert!(!no_cache.is_fresh());
+
+        let validated = entry(&[(header::ETAG, "\"v1\"")]).unwrap();
+        assert!(!validated.is_fresh());
+
+        assert!(entry(&[(header::CACHE_CONTROL, "max-age=60, no-store")]).is_none());
+        assert!(entry(&[(header::CACHE_CONTROL, "max-age=60"), (header::VARY, "*")]).is_none());
+        assert!(entry(&[]).is_none());
+    }
+
+    #[test]
+    fn a_bare_304_keeps_the_freshness_fields() {
+        let mut entry = entry(&[
+            (header::CACHE_CONTROL, "max-age=60"),
+            (header::ETAG, "\"v1\""),
+        ])
+        .unwrap();
+        entry.meta.stored_at -= 120;
+        assert!(!entry.is_fresh());
+
+        assert!(entry.refresh(&HeaderMap::new()));
+        assert_eq!(entry.meta.max_age, Some(60));
+        assert_eq!(entry.meta.etag.as_deref(), Some("\"v1\""));
+        assert!(entry.is_fresh());
+
+        assert!(entry.refresh(&headers(&[
+            (header::CACHE_CONTROL, "no-cache"),
+            (header::ETAG, "\"v2\""),
+        ])));
+        assert_eq!(entry.meta.max_age, None);
+        assert!(entry.meta.no_cache);
+        assert_eq!(entry.meta.etag.as_deref(), Some("\"v2\""));
+        assert!(!entry.is_fresh());
+    }
+}
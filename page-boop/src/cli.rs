@@ -0,0 +1,241 @@
+//! Headless command line interface, for scripts and machines without a display.
+
+use std::io::BufRead;
+
+use page_boop::{
+    Mode,
+    batch::{self, Summary},
+    build_client, fetch_url,
+    network::NetworkSettings,
+    validate_url, websocket,
+};
+
+const USAGE: &str = "usage:
+    page-boop fetch <URL> [--mode title|time|size|outline] [--http] [--json] [--no-cache]
+    page-boop batch [--http] [--parallel N] [URL...]
+    page-boop echo [ADDR]
+
+Without URLs, batch reads one URL per line from stdin.
+echo runs a WebSocket echo server, on 127.0.0.1:9001 by default.
+Proxy and TLS settings come from ALL_PROXY/HTTPS_PROXY/HTTP_PROXY,
+NO_PROXY, PAGE_BOOP_CA_FILE and PAGE_BOOP_INSECURE=1.";
+
+const DEFAULT_PARALLELISM: usize = 4;
+
+/// Run the command line arguments following the binary name.
+///
+/// Returns `None` if the arguments should launch the GUI instead,
+/// otherwise the process exit code.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    let code = match command.as_str() {
+        "fetch" => run_fetch(rest),
+        "batch" => run_batch(rest),
+        "echo" => run_echo(rest),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            0
+        }
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn parse_mode(mode: &str) -> Option<Mode> {
+    match mode {
+        "title" => Some(Mode::Title),
+        "time" => Some(Mode::DownloadTime),
+        "size" => Some(Mode::DownloadSize),
+        "outline" => Some(Mode::Outline),
+        _ => None,
+    }
+}
+
+fn run_fetch(args: &[String]) -> i32 {
+    let mut mode = Mode::Title;
+    let mut secure = true;
+    let mut json = false;
+    let mut use_cache = true;
+    let mut url = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--mode" => match args.next().and_then(|m| parse_mode(m)) {
+                Some(m) => mode = m,
+                None => return usage_error("--mode expects title, time, size or outline"),
+            },
+            "--http" => secure = false,
+            "--json" => json = true,
+            "--no-cache" => use_cache = false,
+            flag if flag.starts_with("--") => {
+                return usage_error(&format!("unknown option {flag}"));
+            }
+            u if url.is_none() => url = Some(u.to_string()),
+            extra => return usage_error(&format!("unexpected argument {extra}")),
+        }
+    }
+    let Some(url) = url else {
+        return usage_error("fetch expects a URL");
+    };
+
+    let parsed = match validate_url(&url, secure) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("{error}");
+            return 1;
+        }
+    };
+    let network = NetworkSettings::from_env();
+    let fetched = block_on(async {
+        let client = build_client(&network).await?;
+        Ok::<_, String>(
+            fetch_url(
+                client,
+                parsed,
+                mode,
+                Default::default(),
+                // A cached body would time the disk, not the download.
+                use_cache && mode != Mode::DownloadTime,
+            )
+            .await,
+        )
+    });
+    let fetched = match fetched {
+        Some(Ok(fetched)) => fetched,
+        Some(Err(error)) => {
+            eprintln!("{error}");
+            return 1;
+        }
+        None => return 1,
+    };
+
+    if json {
+        let value = serde_json::json!({
+            "url": url,
+            "mode": mode.to_string(),
+            "ok": fetched.result.is_ok(),
+            "result": fetched.result.as_ref().unwrap_or_else(|error| error),
+            "cache": fetched.cache.map(|cache| cache.to_string()),
+            "outline": fetched.outline,
+        });
+        println!("{value}");
+    } else {
+        match &fetched.result {
+            Ok(result) => println!("{result}"),
+            Err(error) => eprintln!("{error}"),
+        }
+        if let Some(cache) = fetched.cache {
+            println!("({cache})");
+        }
+        if let Some(outline) = &fetched.outline {
+            for heading in &outline.headings {
+                let indent = "  ".repeat(usize::from(heading.level - 1));
+                println!("{indent}h{} {}", heading.level, heading.text);
+            }
+        }
+    }
+
+    i32::from(fetched.result.is_err())
+}
+
+fn run_batch(args: &[String]) -> i32 {
+    let mut secure = true;
+    let mut parallelism = DEFAULT_PARALLELISM;
+    let mut urls = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--http" => secure = false,
+            "--parallel" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => parallelism = n,
+                None => return usage_error("--parallel expects a number"),
+            },
+            flag if flag.starts_with("--") => {
+                return usage_error(&format!("unknown option {flag}"));
+            }
+            url => urls.push(url.to_string()),
+        }
+    }
+
+    if urls.is_empty() {
+        urls = std::io::stdin()
+            .lock()
+            .lines()
+            .map_while(Result::ok)
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+    }
+
+    let network = NetworkSettings::from_env();
+    let results = block_on(async {
+        let client = build_client(&network).await?;
+        Ok::<_, String>(batch::run_headless(client, urls, secure, parallelism).await)
+    });
+    let results = match results {
+        Some(Ok(results)) => results,
+        Some(Err(error)) => {
+            eprintln!("{error}");
+            return 1;
+        }
+        None => return 1,
+    };
+
+    let mut failed = false;
+    for (url, result) in &results {
+        match result {
+            Ok(m) => println!(
+                "{url}\t{:.2?}\t{}",
+                m.elapsed,
+                humansize::format_size(m.size, humansize::DECIMAL)
+            ),
+            Err(error) => {
+                failed = true;
+                println!("{url}\t{error}");
+            }
+        }
+    }
+
+    let durations = results
+        .iter()
+        .filter_map(|(_, result)| result.as_ref().ok().map(|m| m.elapsed))
+        .collect();
+    if let Some(Summary { min, median, max }) = Summary::from_durations(durations) {
+        println!("min {min:.2?}\tmedian {median:.2?}\tmax {max:.2?}");
+    }
+
+    i32::from(failed)
+}
+
+fn run_echo(args: &[String]) -> i32 {
+    let addr = match args {
+        [] => websocket::ECHO_ADDR.to_string(),
+        [addr] => addr.clone(),
+        _ => return usage_error("echo expects at most one address"),
+    };
+
+    println!("Echoing WebSocket messages on ws://{addr}");
+    // The server only stops when it fails.
+    if let Some(Err(error)) = block_on(websocket::serve_echo(addr)) {
+        eprintln!("{error}");
+    }
+    1
+}
+
+/// Run a future to completion on a fresh Tokio runtime.
+fn block_on<F: Future>(future: F) -> Option<F::Output> {
+    match tokio::runtime::Runtime::new() {
+        Ok(runtime) => Some(runtime.block_on(future)),
+        Err(e) => {
+            eprintln!("Error starting runtime: {e}");
+            None
+        }
+    }
+}
+
+fn usage_error(message: &str) -> i32 {
+    eprintln!("{message}\n\n{USAGE}");
+    2
+}
@@ -1,12 +1,38 @@
 use iced::{
-    Color, Element, Font, Length, Task,
+    Color, Element, Font, Length, Subscription, Task, Theme,
+    task::{self, Sipper, sipper},
     widget::{
-        button, checkbox, column, container, pick_list, row, scrollable, space, text, text_input,
+        button, canvas, checkbox, column, container, pick_list, progress_bar, row, scrollable,
+        slider, space, text, text_editor, text_input,
     },
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use strum::{Display, EnumIter, IntoEnumIterator};
 
+pub mod batch;
+pub mod cache;
+pub mod message_log;
+pub mod network;
+pub mod outline;
+pub mod request;
+pub mod robots;
+pub mod snapshot;
+pub mod timing;
+pub mod tunnel;
+pub mod websocket;
+
+use cache::CacheStatus;
+use message_log::MessageLog;
+use network::{NetworkSettings, SharedClient};
+use outline::Outline;
+use request::{Header, Method, RequestOptions};
+use robots::Inspection;
+use sipper::Sender;
+use timing::{Comparison, Timings, Waterfall};
+
 /// Mono font embedded from the shared fonts directory.
 pub const FIRA_MONO_BYTES: &[u8] = include_bytes!("../../fonts/FiraMono-Regular.ttf");
 
@@ -16,6 +42,7 @@ const FIRA_MONO: Font = Font::with_name("Fira Mono");
 pub struct StyleConfig {
     pub mono_font: Font,
     pub subtitle_color: Color,
+    pub error_color: Color,
     pub text_size: u32,
 }
 
@@ -24,12 +51,13 @@ impl Default for StyleConfig {
         Self {
             mono_font: FIRA_MONO,
             subtitle_color: Color::from_rgb(0.45, 0.45, 0.45),
+            error_color: Color::from_rgb(0.85, 0.25, 0.25),
             text_size: 24,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumIter, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumIter, Serialize, Deserialize)]
 pub enum Mode {
     #[default]
     Title,
@@ -39,25 +67,137 @@ pub enum Mode {
 
     #[strum(serialize = "Download Size")]
     DownloadSize,
+
+    Outline,
+
+    #[strum(serialize = "Cold vs Warm")]
+    Compare,
+
+    WebSocket,
+
+    #[strum(serialize = "Robots & Sitemap")]
+    Robots,
 }
 
-#[derive(Default, Serialize)]
+/// Which part of Page Boop is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumIter)]
+pub enum Tab {
+    #[default]
+    Single,
+    Batch,
+    Settings,
+}
+
+const MAX_RUNS: u8 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UrlAnalyzer {
+    method: Method,
     url: String,
     secure: bool,
     mode: Mode,
 
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<Header>,
+
+    #[serde(skip_serializing_if = "String::is_empty")]
+    body: String,
+
+    /// Number of requests averaged in [`Mode::DownloadTime`].
+    runs: u8,
+
+    use_cache: bool,
+
     #[serde(skip_serializing_if = "String::is_empty")]
     result: String,
 
+    #[serde(skip)]
+    cache_status: Option<CacheStatus>,
+
+    #[serde(skip)]
+    outline: Option<Outline>,
+
+    #[serde(skip)]
+    timings: Option<Timings>,
+
+    #[serde(skip)]
+    comparison: Option<Comparison>,
+
+    /// Messages sent and received in [`Mode::WebSocket`].
+    #[serde(skip)]
+    transcript: Vec<String>,
+
+    #[serde(skip)]
+    inspection: Option<Inspection>,
+
+    #[serde(skip)]
+    progress: Option<Progress>,
+
     #[serde(skip)]
     loading: bool,
 }
 
+impl Default for UrlAnalyzer {
+    fn default() -> Self {
+        Self {
+            method: Method::default(),
+            url: String::new(),
+            secure: false,
+            mode: Mode::default(),
+            headers: Vec::new(),
+            body: String::new(),
+            runs: 1,
+            use_cache: true,
+            result: String::new(),
+            cache_status: None,
+            outline: None,
+            timings: None,
+            comparison: None,
+            transcript: Vec::new(),
+            inspection: None,
+            progress: None,
+            loading: false,
+        }
+    }
+}
+
+impl UrlAnalyzer {
+    fn request_options(&self) -> RequestOptions {
+        RequestOptions {
+            method: self.method,
+            headers: self.headers.clone(),
+            body: self.body.clone(),
+        }
+    }
+}
+
 /// Self-contained "Page Boop" component state.
 pub struct PageBoop {
     model: UrlAnalyzer,
-    message_log: Vec<String>,
+    body_editor: text_editor::Content,
+    batch: batch::Batch,
+    tab: Tab,
+    message_log: MessageLog,
+    /// Log entry whose snapshot is currently shown, if travelling in time.
+    selected: Option<usize>,
+    log_path: String,
+    log_status: Option<Result<String, String>>,
+    /// Editable RON view of the model.
+    ron_editor: text_editor::Content,
+    /// Parse error of the RON editor contents, if any.
+    ron_error: Option<String>,
+    snapshot_path: String,
+    snapshot_status: Option<Result<String, String>>,
+    network: NetworkSettings,
+    /// Shared by all requests so connections are kept alive; built on first
+    /// use and dropped when the network settings change.
+    client: SharedClient,
+    /// WebSocket URL subscribed to, while connecting or connected.
+    socket: Option<url::Url>,
+    connection: Option<websocket::Connection>,
+    socket_draft: String,
+    echo_server: Option<task::Handle>,
     style: StyleConfig,
 }
 
@@ -66,7 +206,23 @@ impl PageBoop {
     pub fn new() -> Self {
         Self {
             model: UrlAnalyzer::default(),
-            message_log: Vec::new(),
+            body_editor: text_editor::Content::new(),
+            batch: batch::Batch::default(),
+            tab: Tab::default(),
+            message_log: MessageLog::default(),
+            selected: None,
+            log_path: message_log::DEFAULT_EXPORT_PATH.to_string(),
+            log_status: None,
+            ron_editor: text_editor::Content::with_text(&snapshot::to_ron(&UrlAnalyzer::default())),
+            ron_error: None,
+            snapshot_path: snapshot::DEFAULT_PATH.to_string(),
+            snapshot_status: None,
+            network: NetworkSettings::from_env(),
+            client: SharedClient::default(),
+            socket: None,
+            connection: None,
+            socket_draft: String::new(),
+            echo_server: None,
             style: StyleConfig::default(),
         }
     }
@@ -74,70 +230,590 @@ impl PageBoop {
     /// Create a new PageBoop with custom styling.
     pub fn with_style(style: StyleConfig) -> Self {
         Self {
-            model: UrlAnalyzer::default(),
-            message_log: Vec::new(),
             style,
+            ..Self::new()
         }
     }
 
-    fn log_message(&mut self, msg: String) {
-        self.message_log.push(msg);
+    fn log_message(&mut self, message: Message) {
+        // Acting on a selected entry branches off from it; the entries after
+        // it no longer lead to the current model.
+        if let Some(index) = self.selected.take() {
+            self.message_log.truncate_after(index);
+        }
+        self.message_log.push(message, self.model.clone());
+    }
+
+    /// Whether the mode would send a request that isn't safe to repeat
+    /// several times, which it refuses to do.
+    fn repeats_unsafe_request(&self) -> bool {
+        matches!(self.model.mode, Mode::DownloadTime | Mode::Compare)
+            && !self.model.method.is_safe()
+    }
+
+    /// Restore the model to a snapshot, keeping the editors in sync. An open
+    /// WebSocket is dropped, as the snapshot was not taken on it.
+    fn restore(&mut self, model: UrlAnalyzer) {
+        self.socket = None;
+        self.connection = None;
+        self.body_editor = text_editor::Content::with_text(&model.body);
+        self.model = model;
+        self.sync_ron_editor();
+    }
+
+    /// Show the current model in the RON editor, discarding pending edits.
+    fn sync_ron_editor(&mut self) {
+        self.ron_editor = text_editor::Content::with_text(&snapshot::to_ron(&self.model));
+        self.ron_error = None;
+    }
+
+    /// Show the model in the RON editor if its snapshot changed from
+    /// `before`, unless the editor holds text that does not parse yet.
+    fn refresh_ron_editor(&mut self, before: &str) {
+        if self.ron_error.is_some() {
+            return;
+        }
+        let ron = snapshot::to_ron(&self.model);
+        if ron != before {
+            self.ron_editor = text_editor::Content::with_text(&ron);
+        }
     }
 
     /// Process a message and return an action.
     #[must_use]
     pub fn update(&mut self, message: Message) -> Action {
+        let logged = message.is_logged().then(|| message.clone());
+        let before = logged.is_some().then(|| snapshot::to_ron(&self.model));
+        let action = self.apply(message);
+        if let (Some(message), Some(before)) = (logged, before) {
+            self.log_message(message);
+            self.refresh_ron_editor(&before);
+        }
+        action
+    }
+
+    fn apply(&mut self, message: Message) -> Action {
         match message {
             Message::UrlChanged(url) => {
-                self.log_message(format!("UrlChanged({:?})", url));
                 self.model.url = url;
                 Action::None
             }
+            Message::MethodChanged(method) => {
+                self.model.method = method;
+                Action::None
+            }
+            Message::HeaderAdded => {
+                self.model.headers.push(Header::default());
+                Action::None
+            }
+            Message::HeaderRemoved(index) => {
+                if index < self.model.headers.len() {
+                    self.model.headers.remove(index);
+                }
+                Action::None
+            }
+            Message::HeaderNameChanged(index, name) => {
+                if let Some(header) = self.model.headers.get_mut(index) {
+                    header.name = name;
+                }
+                Action::None
+            }
+            Message::HeaderValueChanged(index, value) => {
+                if let Some(header) = self.model.headers.get_mut(index) {
+                    header.value = value;
+                }
+                Action::None
+            }
+            Message::BodyEdited(action) => {
+                let before = action.is_edit().then(|| snapshot::to_ron(&self.model));
+                self.body_editor.perform(action);
+                self.model.body = self.body_editor.text();
+                // Log the resulting text rather than the editor action, so
+                // replaying does not depend on the cursor position.
+                if let Some(before) = before {
+                    self.log_message(Message::BodyChanged(self.model.body.clone()));
+                    self.refresh_ron_editor(&before);
+                }
+                Action::None
+            }
+            Message::BodyChanged(body) => {
+                self.body_editor = text_editor::Content::with_text(&body);
+                self.model.body = body;
+                Action::None
+            }
             Message::SecureChanged(secure) => {
-                self.log_message(format!("SecureChanged({})", secure));
                 self.model.secure = secure;
                 Action::None
             }
+            Message::UseCacheChanged(use_cache) => {
+                self.model.use_cache = use_cache;
+                Action::None
+            }
             Message::ModeChanged(mode) => {
-                self.log_message(format!("ModeChanged({})", mode));
                 self.model.mode = mode;
                 Action::None
             }
-            Message::Action => {
-                self.log_message("Action".to_string());
-                if self.model.url.is_empty() {
-                    self.model.result = "Please enter a URL".to_string();
+            Message::Action if self.model.mode == Mode::WebSocket => {
+                if let Some(url) = self.socket.take() {
+                    self.connection = None;
+                    self.model
+                        .transcript
+                        .push(format!("Disconnected from {url}"));
+                    return Action::None;
+                }
+                match validate_url(&self.model.url, self.model.secure) {
+                    Ok(url) => {
+                        let url = websocket::ws_url(url);
+                        self.model
+                            .transcript
+                            .push(format!("Connecting to {url}..."));
+                        self.socket = Some(url);
+                    }
+                    Err(error) => self.model.result = error,
+                }
+                Action::None
+            }
+            Message::Action => match validate_url(&self.model.url, self.model.secure) {
+                Err(error) => {
+                    self.model.result = error;
                     Action::None
-                } else {
+                }
+                Ok(parsed) => {
                     self.model.loading = true;
                     self.model.result.clear();
-                    let url = self.model.url.clone();
-                    let secure = self.model.secure;
+                    self.model.cache_status = None;
+                    self.model.outline = None;
+                    self.model.timings = None;
+                    self.model.comparison = None;
+                    self.model.inspection = None;
+                    self.model.progress = None;
                     let mode = self.model.mode;
-                    Action::Run(Task::perform(fetch_url(url, secure, mode), Message::Result))
+                    let options = self.model.request_options();
+                    let network = self.network.clone();
+                    // Built in the task, so reading certificates never blocks the UI.
+                    let client = self.client.get(&network);
+                    match mode {
+                        Mode::DownloadTime => Action::Run(Task::perform(
+                            timing::measure_average(parsed, self.model.runs, options, network),
+                            Message::Timings,
+                        )),
+                        Mode::Compare => Action::Run(Task::perform(
+                            async move {
+                                let fresh = build_client(&network).await?;
+                                timing::compare_connections(fresh, client.await?, parsed, options)
+                                    .await
+                            },
+                            Message::Compared,
+                        )),
+                        Mode::Robots => Action::Run(Task::perform(
+                            async move { robots::inspect(client.await?, parsed).await },
+                            Message::Inspected,
+                        )),
+                        _ => {
+                            let use_cache = self.model.use_cache;
+                            Action::Run(Task::sip(
+                                sipper(move |progress| async move {
+                                    match client.await {
+                                        Ok(client) => {
+                                            fetch_url(client, parsed, mode, options, use_cache)
+                                                .run(progress)
+                                                .await
+                                        }
+                                        Err(error) => Fetched {
+                                            result: Err(error),
+                                            cache: None,
+                                            outline: None,
+                                        },
+                                    }
+                                }),
+                                Message::Progress,
+                                Message::Result,
+                            ))
+                        }
+                    }
                 }
+            },
+            Message::Inspected(result) => {
+                self.model.loading = false;
+                match result {
+                    Ok(inspection) => {
+                        let verdict = if inspection.verdict.allowed {
+                            "allowed"
+                        } else {
+                            "disallowed"
+                        };
+                        self.model.result = format!(
+                            "{} is {verdict} for {}",
+                            inspection.path,
+                            robots::product_token()
+                        );
+                        self.model.inspection = Some(inspection);
+                    }
+                    Err(error) => self.model.result = error,
+                }
+                Action::None
             }
-            Message::Result(result) => {
-                self.log_message(format!("Result({:?})", result));
+            Message::SitemapUrlSelected(url) => {
+                self.model.url = url;
+                self.model.mode = Mode::Title;
+                self.apply(Message::Action)
+            }
+            Message::RunsChanged(runs) => {
+                self.model.runs = runs;
+                Action::None
+            }
+            Message::Timings(result) => {
                 self.model.loading = false;
-                self.model.result = result;
+                match result {
+                    Ok(timings) => {
+                        self.model.result = format!("Download time: {:.2?}", timings.total());
+                        self.model.timings = Some(timings);
+                    }
+                    Err(error) => self.model.result = error,
+                }
                 Action::None
             }
+            Message::Compared(result) => {
+                self.model.loading = false;
+                match result {
+                    Ok(comparison) => {
+                        self.model.result = format!(
+                            "Cold: {:.2?} · Warm: {:.2?} ({:.1}× faster)",
+                            comparison.cold,
+                            comparison.warm,
+                            comparison.speedup()
+                        );
+                        self.model.comparison = Some(comparison);
+                    }
+                    Err(error) => self.model.result = error,
+                }
+                Action::None
+            }
+            Message::Socket(event) => {
+                match event {
+                    websocket::Event::Connected(connection) => {
+                        self.connection = Some(connection);
+                        self.model.transcript.push("Connected".to_string());
+                    }
+                    websocket::Event::Received(text) => {
+                        self.model.transcript.push(format!("← {text}"));
+                    }
+                    websocket::Event::Disconnected(reason) => {
+                        self.socket = None;
+                        self.connection = None;
+                        self.model.transcript.push(reason);
+                    }
+                }
+                Action::None
+            }
+            Message::SocketDraftChanged(draft) => {
+                self.socket_draft = draft;
+                Action::None
+            }
+            Message::SocketSend(text) => {
+                let Some(connection) = self.connection.clone() else {
+                    return Action::None;
+                };
+                self.socket_draft.clear();
+                self.model.transcript.push(format!("→ {text}"));
+                // A failed send means the connection is gone, which the
+                // subscription reports on its own.
+                Action::Run(Task::future(connection.send(text)).discard())
+            }
+            Message::StartEchoServer => {
+                let (task, handle) = Task::perform(
+                    websocket::serve_echo(websocket::ECHO_ADDR.to_string()),
+                    Message::EchoServerStopped,
+                )
+                .abortable();
+                self.echo_server = Some(handle);
+                // Through a message of its own, so the log records the new URL.
+                let url = Message::UrlChanged(format!("ws://{}", websocket::ECHO_ADDR));
+                Action::Run(Task::batch([task, Task::done(url)]))
+            }
+            Message::StopEchoServer => {
+                if let Some(handle) = self.echo_server.take() {
+                    handle.abort();
+                }
+                Action::None
+            }
+            Message::EchoServerStopped(result) => {
+                self.echo_server = None;
+                if let Err(error) = result {
+                    self.model.transcript.push(error);
+                }
+                Action::None
+            }
+            Message::Progress(progress) => {
+                self.model.progress = Some(progress);
+                Action::None
+            }
+            Message::Result(fetched) => {
+                self.model.loading = false;
+                self.model.progress = None;
+                self.model.result = fetched.result.unwrap_or_else(|error| error);
+                self.model.cache_status = fetched.cache;
+                self.model.outline = fetched.outline;
+                Action::None
+            }
+            Message::LogEntrySelected(index) => {
+                if let Some(entry) = self.message_log.get(index) {
+                    let model = entry.model.clone();
+                    self.restore(model);
+                    self.selected = Some(index);
+                }
+                Action::None
+            }
+            Message::ReplayFrom(index) => {
+                let start = self.message_log.before(index);
+                self.restore(start);
+
+                // Re-running update must land on the same state; the
+                // recorded results stand in for the tasks dropped here.
+                for entry in self.message_log.drain_from(index) {
+                    if entry.message.touches_socket(&entry.model) {
+                        // Replaying must not open or close a real connection,
+                        // so the recorded state is taken as is.
+                        self.model = entry.model;
+                        self.log_message(entry.message);
+                    } else {
+                        let _ = self.update(entry.message);
+                    }
+                }
+                self.sync_ron_editor();
+                Action::None
+            }
+            Message::LogCapacityChanged(capacity) => {
+                self.message_log.set_capacity(usize::from(capacity));
+                // Indices shift when old entries are dropped.
+                self.selected = None;
+                Action::None
+            }
+            Message::LogKindToggled(kind) => {
+                self.message_log.toggle_kind(kind);
+                Action::None
+            }
+            Message::LogPathChanged(path) => {
+                self.log_path = path;
+                Action::None
+            }
+            Message::ExportLog => Action::Run(Task::perform(
+                message_log::export(self.log_path.clone().into(), self.message_log.to_text()),
+                Message::LogExported,
+            )),
+            Message::LogExported(result) => {
+                self.log_status =
+                    Some(result.map(|path| format!("Exported to {}", path.display())));
+                Action::None
+            }
+            Message::RonEdited(action) => {
+                let is_edit = action.is_edit();
+                self.ron_editor.perform(action);
+                if is_edit {
+                    match snapshot::from_ron(&self.ron_editor.text()) {
+                        Ok(model) => {
+                            self.ron_error = None;
+                            let model = Box::new(model);
+                            // Logged directly so the editor keeps its cursor.
+                            let _ = self.apply(Message::StateEdited(model.clone()));
+                            self.log_message(Message::StateEdited(model));
+                        }
+                        Err(error) => self.ron_error = Some(error),
+                    }
+                }
+                Action::None
+            }
+            Message::StateEdited(model) => {
+                // Results in flight are not part of the snapshot; keep them.
+                self.body_editor = text_editor::Content::with_text(&model.body);
+                self.model = UrlAnalyzer {
+                    cache_status: self.model.cache_status,
+                    outline: self.model.outline.take(),
+                    timings: self.model.timings.take(),
+                    comparison: self.model.comparison.take(),
+                    transcript: std::mem::take(&mut self.model.transcript),
+                    inspection: self.model.inspection.take(),
+                    progress: self.model.progress.take(),
+                    loading: self.model.loading,
+                    ..*model
+                };
+                Action::None
+            }
+            Message::SnapshotPathChanged(path) => {
+                self.snapshot_path = path;
+                Action::None
+            }
+            Message::SaveSnapshot => Action::Run(Task::perform(
+                snapshot::save(self.snapshot_path.clone().into(), self.model.clone()),
+                Message::SnapshotSaved,
+            )),
+            Message::SnapshotSaved(result) => {
+                self.snapshot_status =
+                    Some(result.map(|path| format!("Saved to {}", path.display())));
+                Action::None
+            }
+            Message::LoadSnapshot => Action::Run(Task::perform(
+                snapshot::load(self.snapshot_path.clone().into()),
+                |result| Message::SnapshotLoaded(result.map(Box::new)),
+            )),
+            Message::SnapshotLoaded(result) => {
+                match result {
+                    Ok(model) => {
+                        self.snapshot_status = Some(Ok(format!("Loaded {}", self.snapshot_path)));
+                        self.restore(*model);
+                    }
+                    Err(error) => self.snapshot_status = Some(Err(error)),
+                }
+                Action::None
+            }
+            Message::TabChanged(tab) => {
+                self.tab = tab;
+                Action::None
+            }
+            Message::Batch(msg) => match self.batch.update(msg, self.client.get(&self.network)) {
+                batch::Action::None => Action::None,
+                batch::Action::Run(task) => Action::Run(task.map(Message::Batch)),
+            },
+            Message::Network(msg) => {
+                self.network.update(msg);
+                self.client = SharedClient::default();
+                Action::None
+            }
+        }
+    }
+
+    /// The WebSocket connection, while one is open or being opened.
+    pub fn subscription(&self) -> Subscription<Message> {
+        match &self.socket {
+            Some(url) => {
+                Subscription::run_with((url.clone(), self.network.clone()), websocket::connect)
+                    .map(Message::Socket)
+            }
+            None => Subscription::none(),
         }
     }
 
     /// Render the Page Boop UI.
     pub fn view(&self) -> Element<'_, Message> {
+        let tabs = row(Tab::iter().map(|tab| {
+            let style = if tab == self.tab {
+                button::primary
+            } else {
+                button::secondary
+            };
+            button(text(tab.to_string()))
+                .on_press(Message::TabChanged(tab))
+                .style(style)
+                .into()
+        }))
+        .spacing(8);
+
+        let content = match self.tab {
+            Tab::Single => self.view_single(),
+            Tab::Batch => self.batch.view(&self.style).map(Message::Batch),
+            Tab::Settings => self.network.view(&self.style).map(Message::Network),
+        };
+
+        column![tabs, space().height(16), content]
+            .width(iced::Fill)
+            .into()
+    }
+
+    fn view_single(&self) -> Element<'_, Message> {
         let mono = self.style.mono_font;
         let subtitle = self.style.subtitle_color;
         let ts = self.style.text_size;
 
         let mode_options: Vec<Mode> = Mode::iter().collect();
+        let method_options: Vec<Method> = Method::iter().collect();
 
-        let get_button = if self.model.loading {
-            button("Loading...")
+        let header_rows = self.model.headers.iter().enumerate().map(|(i, header)| {
+            row![
+                text_input("Header", &header.name)
+                    .on_input(move |name| Message::HeaderNameChanged(i, name))
+                    .font(mono)
+                    .width(Length::FillPortion(1)),
+                text_input("Value", &header.value)
+                    .on_input(move |value| Message::HeaderValueChanged(i, value))
+                    .font(mono)
+                    .width(Length::FillPortion(2)),
+                button("✕")
+                    .on_press(Message::HeaderRemoved(i))
+                    .style(button::danger),
+            ]
+            .spacing(8)
+            .align_y(iced::Alignment::Center)
+            .into()
+        });
+        let headers = column(header_rows)
+            .push(button("+ Header").on_press(Message::HeaderAdded))
+            .spacing(8);
+
+        let body_editor: Element<'_, Message> = if self.model.method.has_body() {
+            text_editor(&self.body_editor)
+                .placeholder("Request body")
+                .on_action(Message::BodyEdited)
+                .font(mono)
+                .height(100)
+                .into()
         } else {
+            space().into()
+        };
+
+        let validated = validate_url(&self.model.url, self.model.secure);
+        let invalid = validated.is_err() && !self.model.url.is_empty();
+
+        let get_button = if self.model.mode == Mode::WebSocket {
+            match (&self.socket, &self.connection) {
+                (Some(_), Some(_)) => button("Disconnect").on_press(Message::Action),
+                (Some(_), None) => button("Connecting...").on_press(Message::Action),
+                (None, _) if validated.is_ok() => button("Connect").on_press(Message::Action),
+                (None, _) => button("Connect"),
+            }
+        } else if self.model.loading {
+            button("Loading...")
+        } else if validated.is_ok() && !self.repeats_unsafe_request() {
             button("Get").on_press(Message::Action)
+        } else {
+            button("Get")
+        };
+
+        let error_color = self.style.error_color;
+        let url_feedback: Element<'_, Message> = match &validated {
+            Ok(url) => {
+                let requested = match unicode_host(url) {
+                    Some(unicode) => format!("→ {url}  ({unicode})"),
+                    None => format!("→ {url}"),
+                };
+                text(requested).size(18).font(mono).color(subtitle).into()
+            }
+            Err(error) if invalid => text(error.clone())
+                .size(18)
+                .font(mono)
+                .color(error_color)
+                .into(),
+            Err(_) => space().into(),
+        };
+
+        let insecure_warning: Element<'_, Message> = if self.network.accept_invalid_certs {
+            text("Certificate verification is off (see Settings)")
+                .size(18)
+                .color(error_color)
+                .into()
+        } else {
+            space().into()
+        };
+
+        let method_warning: Element<'_, Message> = if self.repeats_unsafe_request() {
+            text(format!(
+                "{} sends the request several times, so it only runs GET and HEAD",
+                self.model.mode
+            ))
+            .size(18)
+            .color(error_color)
+            .into()
+        } else {
+            space().into()
         };
 
         let result_text = if self.model.loading {
@@ -148,10 +824,277 @@ impl PageBoop {
             "Enter a URL and click Get".to_string()
         };
 
-        // RON state visualization
-        let ron_config = ron::ser::PrettyConfig::default();
-        let state_ron = ron::ser::to_string_pretty(&self.model, ron_config)
-            .unwrap_or_else(|e| format!("Error: {e}"));
+        let runs_row: Element<'_, Message> = if self.model.mode == Mode::DownloadTime {
+            row![
+                text(format!("Runs: {}", self.model.runs)),
+                slider(1..=MAX_RUNS, self.model.runs, Message::RunsChanged).width(200),
+            ]
+            .spacing(12)
+            .align_y(iced::Alignment::Center)
+            .into()
+        } else {
+            space().into()
+        };
+
+        let progress: Element<'_, Message> = match &self.model.progress {
+            Some(progress) if self.model.loading => {
+                let received = humansize::format_size(progress.received, humansize::DECIMAL);
+                let throughput =
+                    humansize::format_size(progress.throughput as u64, humansize::DECIMAL);
+                let label = match (progress.total, progress.percent()) {
+                    (Some(total), Some(percent)) => format!(
+                        "{received} of {} ({percent:.0}%) · {throughput}/s",
+                        humansize::format_size(total, humansize::DECIMAL)
+                    ),
+                    _ => format!("{received} · {throughput}/s"),
+                };
+                column![
+                    progress_bar(0.0..=100.0, progress.percent().unwrap_or(0.0)),
+                    text(label).font(mono).color(subtitle),
+                ]
+                .spacing(4)
+                .into()
+            }
+            _ => space().into(),
+        };
+
+        let cache_badge: Element<'_, Message> = match self.model.cache_status {
+            Some(status) if !self.model.loading => container(text(status.to_string()).size(18))
+                .padding([2, 8])
+                .style(move |theme: &Theme| {
+                    let palette = theme.extended_palette();
+                    let pair = match status {
+                        CacheStatus::Hit => palette.success.base,
+                        CacheStatus::Revalidated => palette.primary.base,
+                        CacheStatus::Miss => palette.secondary.base,
+                    };
+                    container::Style {
+                        background: Some(pair.color.into()),
+                        text_color: Some(pair.text),
+                        border: iced::border::rounded(4),
+                        ..Default::default()
+                    }
+                })
+                .into(),
+            _ => space().into(),
+        };
+
+        let comparison: Element<'_, Message> = match &self.model.comparison {
+            Some(comparison) if !self.model.loading => {
+                let longest = comparison.cold.max(comparison.warm).as_secs_f32();
+                let bar = |label: &'static str, duration: Duration| {
+                    row![
+                        text(label).font(mono).color(subtitle).width(80),
+                        progress_bar(0.0..=longest, duration.as_secs_f32()),
+                        text(format!("{duration:.1?}")).font(mono).width(110),
+                    ]
+                    .spacing(12)
+                    .align_y(iced::Alignment::Center)
+                };
+                column![
+                    bar("Cold", comparison.cold),
+                    bar("Warm", comparison.warm),
+                    text("A warm request skips DNS, connect and the TLS handshake.")
+                        .size(18)
+                        .color(subtitle),
+                ]
+                .spacing(8)
+                .into()
+            }
+            _ => space().into(),
+        };
+
+        let socket_pane: Element<'_, Message> = if self.model.mode == Mode::WebSocket {
+            let transcript = column(
+                self.model
+                    .transcript
+                    .iter()
+                    .map(|line| text(line).size(20).font(mono).into()),
+            )
+            .spacing(4);
+
+            let send = row![
+                text_input("Message", &self.socket_draft)
+                    .on_input(Message::SocketDraftChanged)
+                    .on_submit_maybe(
+                        self.connection
+                            .is_some()
+                            .then(|| Message::SocketSend(self.socket_draft.clone()))
+                    )
+                    .font(mono),
+                button("Send").on_press_maybe(
+                    self.connection
+                        .is_some()
+                        .then(|| Message::SocketSend(self.socket_draft.clone()))
+                ),
+            ]
+            .spacing(8);
+
+            let echo_button = match self.echo_server {
+                Some(_) => button(text(format!("Stop echo server ({})", websocket::ECHO_ADDR)))
+                    .on_press(Message::StopEchoServer)
+                    .style(button::secondary),
+                None => button("Start local echo server")
+                    .on_press(Message::StartEchoServer)
+                    .style(button::secondary),
+            };
+
+            column![
+                container(scrollable(transcript).anchor_bottom().height(180))
+                    .width(iced::Fill)
+                    .padding(12)
+                    .style(container::rounded_box),
+                send,
+                echo_button,
+            ]
+            .spacing(8)
+            .into()
+        } else {
+            space().into()
+        };
+
+        let robots_pane: Element<'_, Message> = match &self.model.inspection {
+            Some(inspection) if !self.model.loading => {
+                let reason = match (&inspection.verdict.agent, &inspection.verdict.rule) {
+                    (Some(agent), Some(rule)) => format!("User-agent: {agent} → {rule}"),
+                    (Some(agent), None) => format!("User-agent: {agent} → no matching rule"),
+                    (None, _) if inspection.robots.is_none() => {
+                        format!("No {}", inspection.robots_url)
+                    }
+                    (None, _) => "No group applies".to_string(),
+                };
+
+                let groups = column(
+                    inspection
+                        .robots
+                        .iter()
+                        .flat_map(|robots| &robots.groups)
+                        .map(|group| {
+                            let agents = group
+                                .agents
+                                .iter()
+                                .map(|agent| format!("User-agent: {agent}"));
+                            let rules = group.rules.iter().map(|rule| format!("  {rule}"));
+                            text(agents.chain(rules).collect::<Vec<_>>().join("\n"))
+                                .size(18)
+                                .font(mono)
+                                .into()
+                        }),
+                )
+                .spacing(12);
+
+                let sitemap: Element<'_, Message> = match &inspection.sitemap {
+                    Ok(urls) if urls.is_empty() => {
+                        text("No sitemap entries").size(18).color(subtitle).into()
+                    }
+                    Ok(urls) => column(urls.iter().map(|url| {
+                        button(text(url).size(18).font(mono))
+                            .on_press(Message::SitemapUrlSelected(url.clone()))
+                            .style(button::text)
+                            .into()
+                    }))
+                    .into(),
+                    Err(error) => text(error).size(18).color(error_color).into(),
+                };
+
+                column![
+                    text(reason).font(mono).color(subtitle),
+                    row![
+                        container(scrollable(groups).height(220))
+                            .width(Length::FillPortion(1))
+                            .padding(12)
+                            .style(container::rounded_box),
+                        column![
+                            text(inspection.sitemap_url.as_deref().unwrap_or("Sitemap"))
+                                .size(18)
+                                .font(mono)
+                                .color(subtitle),
+                            container(scrollable(sitemap).height(190))
+                                .width(iced::Fill)
+                                .padding(12)
+                                .style(container::rounded_box),
+                        ]
+                        .spacing(4)
+                        .width(Length::FillPortion(1)),
+                    ]
+                    .spacing(20),
+                ]
+                .spacing(8)
+                .into()
+            }
+            _ => space().into(),
+        };
+
+        let outline_pane: Element<'_, Message> = match &self.model.outline {
+            Some(outline) if !self.model.loading => {
+                let headings = column(outline.headings.iter().map(|heading| {
+                    let indent = f32::from(heading.level - 1) * 24.0;
+                    row![
+                        space().width(indent),
+                        text(format!("h{}", heading.level))
+                            .size(16)
+                            .font(mono)
+                            .color(subtitle),
+                        text(&heading.text).size(20),
+                    ]
+                    .spacing(8)
+                    .align_y(iced::Alignment::Center)
+                    .into()
+                }))
+                .spacing(4);
+
+                row![
+                    container(scrollable(headings).height(220))
+                        .width(Length::FillPortion(1))
+                        .padding(12)
+                        .style(container::rounded_box),
+                    container(scrollable(text(&outline.preview).size(18)).height(220))
+                        .width(Length::FillPortion(1))
+                        .padding(12)
+                        .style(container::rounded_box),
+                ]
+                .spacing(20)
+                .into()
+            }
+            _ => space().into(),
+        };
+
+        let waterfall: Element<'_, Message> = match &self.model.timings {
+            Some(timings) if !self.model.loading => canvas(Waterfall {
+                timings,
+                text_color: subtitle,
+            })
+            .width(iced::Fill)
+            .height(Waterfall::height(timings))
+            .into(),
+            _ => space().into(),
+        };
+
+        // RON state, editable in place
+        let ron_status: Element<'_, Message> = match &self.ron_error {
+            Some(error) => text(error)
+                .size(18)
+                .font(mono)
+                .color(self.style.error_color)
+                .into(),
+            None => space().into(),
+        };
+
+        let snapshot_row = row![
+            text_input(snapshot::DEFAULT_PATH, &self.snapshot_path)
+                .on_input(Message::SnapshotPathChanged)
+                .font(mono),
+            button("Save").on_press(Message::SaveSnapshot),
+            button("Load").on_press(Message::LoadSnapshot),
+        ]
+        .spacing(8)
+        .align_y(iced::Alignment::Center);
+
+        let snapshot_status: Element<'_, Message> = match &self.snapshot_status {
+            Some(Ok(status)) => text(status).size(18).color(subtitle).into(),
+            Some(Err(error)) => text(error).size(18).color(self.style.error_color).into(),
+            None => space().into(),
+        };
 
         // Message log
         let message_log_content: Element<'_, Message> = if self.message_log.is_empty() {
@@ -162,9 +1105,28 @@ impl PageBoop {
         } else {
             column(
                 self.message_log
-                    .iter()
-                    .map(|msg| {
-                        row![text(msg).size(20).font(mono), space().width(Length::Fill)].into()
+                    .visible()
+                    .map(|(i, entry)| {
+                        let style = if self.selected == Some(i) {
+                            button::primary
+                        } else {
+                            button::text
+                        };
+                        button(
+                            row![
+                                text(message_log::timestamp(entry.at))
+                                    .size(16)
+                                    .font(mono)
+                                    .color(subtitle)
+                                    .width(90),
+                                text(entry.message.to_string()).size(20).font(mono),
+                            ]
+                            .align_y(iced::Alignment::Center),
+                        )
+                        .on_press(Message::LogEntrySelected(i))
+                        .style(style)
+                        .width(Length::Fill)
+                        .into()
                     })
                     .collect::<Vec<_>>(),
             )
@@ -172,43 +1134,137 @@ impl PageBoop {
             .into()
         };
 
+        // One toggle per kind of message; hidden kinds are greyed out.
+        let log_filter = row(self.message_log.kinds().into_iter().map(|kind| {
+            let style = if self.message_log.is_hidden(kind) {
+                button::secondary
+            } else {
+                button::primary
+            };
+            button(text(kind).size(16).font(mono))
+                .on_press(Message::LogKindToggled(kind.to_string()))
+                .style(style)
+                .padding([2, 8])
+                .into()
+        }))
+        .spacing(4)
+        .wrap();
+
+        let capacity = self.message_log.capacity();
+        let log_controls = row![
+            text(format!("Keep {capacity}")).size(18).color(subtitle),
+            slider(
+                10..=message_log::MAX_CAPACITY as u16,
+                capacity as u16,
+                Message::LogCapacityChanged
+            )
+            .step(10u16)
+            .width(160),
+            text_input(message_log::DEFAULT_EXPORT_PATH, &self.log_path)
+                .on_input(Message::LogPathChanged)
+                .font(mono),
+            button("Export").on_press(Message::ExportLog),
+        ]
+        .spacing(8)
+        .align_y(iced::Alignment::Center);
+
+        let log_status: Element<'_, Message> = match &self.log_status {
+            Some(Ok(status)) => text(status).size(18).color(subtitle).into(),
+            Some(Err(error)) => text(error).size(18).color(self.style.error_color).into(),
+            None => space().into(),
+        };
+
+        let replay_button = match self.selected {
+            Some(index) => button("Replay from here").on_press(Message::ReplayFrom(index)),
+            None => button("Replay from here"),
+        };
+
         column![
             // Input row
             row![
+                pick_list(
+                    method_options,
+                    Some(self.model.method),
+                    Message::MethodChanged
+                ),
                 text_input("Enter URL (e.g. example.com)", &self.model.url)
                     .on_input(Message::UrlChanged)
-                    .on_submit(Message::Action),
+                    .on_submit(Message::Action)
+                    .style(move |theme: &Theme, status| {
+                        let style = text_input::default(theme, status);
+                        if invalid {
+                            text_input::Style {
+                                border: style.border.color(error_color),
+                                ..style
+                            }
+                        } else {
+                            style
+                        }
+                    }),
                 checkbox(self.model.secure)
                     .label("HTTPS")
                     .on_toggle(Message::SecureChanged),
+                checkbox(self.model.use_cache)
+                    .label("Cache")
+                    .on_toggle(Message::UseCacheChanged),
                 pick_list(mode_options, Some(self.model.mode), Message::ModeChanged),
                 get_button,
             ]
             .spacing(12)
             .align_y(iced::Alignment::Center),
+            url_feedback,
+            insecure_warning,
+            method_warning,
+            runs_row,
+            space().height(12),
+            headers,
+            body_editor,
             space().height(24),
             // Result
-            text(result_text).size(ts),
+            row![text(result_text).size(ts), cache_badge]
+                .spacing(12)
+                .align_y(iced::Alignment::Center),
+            progress,
+            waterfall,
+            comparison,
+            socket_pane,
+            robots_pane,
+            outline_pane,
             space().height(36),
             // State and messages side by side
             row![
                 column![
                     text("Current State").size(22).font(mono).color(subtitle),
                     space().height(8),
-                    container(text(state_ron).size(20).font(mono))
-                        .width(iced::Fill)
-                        .padding(12)
-                        .style(container::rounded_box),
+                    text_editor(&self.ron_editor)
+                        .on_action(Message::RonEdited)
+                        .font(mono)
+                        .size(20)
+                        .padding(12),
+                    ron_status,
+                    space().height(8),
+                    snapshot_row,
+                    snapshot_status,
                 ]
                 .height(iced::Fill)
                 .width(Length::FillPortion(1)),
                 column![
-                    text("Recent Messages").size(22).font(mono).color(subtitle),
+                    row![
+                        text("Recent Messages").size(22).font(mono).color(subtitle),
+                        space().width(Length::Fill),
+                        replay_button,
+                    ]
+                    .align_y(iced::Alignment::Center),
                     space().height(8),
-                    container(scrollable(message_log_content).height(150))
+                    container(scrollable(message_log_content).anchor_bottom().height(150))
                         .width(iced::Fill)
                         .padding(12)
                         .style(container::rounded_box),
+                    space().height(8),
+                    log_filter,
+                    space().height(8),
+                    log_controls,
+                    log_status,
                 ]
                 .height(iced::Fill)
                 .width(Length::FillPortion(1)),
@@ -228,11 +1284,178 @@ impl Default for PageBoop {
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    MethodChanged(Method),
     UrlChanged(String),
+    HeaderAdded,
+    HeaderRemoved(usize),
+    HeaderNameChanged(usize, String),
+    HeaderValueChanged(usize, String),
+    BodyEdited(text_editor::Action),
+    BodyChanged(String),
     SecureChanged(bool),
+    UseCacheChanged(bool),
     ModeChanged(Mode),
     Action,
-    Result(String),
+    Progress(Progress),
+    Result(Fetched),
+    RunsChanged(u8),
+    Timings(Result<Timings, String>),
+    Compared(Result<Comparison, String>),
+    Inspected(Result<Inspection, String>),
+    SitemapUrlSelected(String),
+    Socket(websocket::Event),
+    SocketDraftChanged(String),
+    SocketSend(String),
+    StartEchoServer,
+    StopEchoServer,
+    EchoServerStopped(Result<(), String>),
+    LogEntrySelected(usize),
+    ReplayFrom(usize),
+    LogCapacityChanged(u16),
+    LogKindToggled(String),
+    LogPathChanged(String),
+    ExportLog,
+    LogExported(Result<PathBuf, String>),
+    RonEdited(text_editor::Action),
+    StateEdited(Box<UrlAnalyzer>),
+    SnapshotPathChanged(String),
+    SaveSnapshot,
+    SnapshotSaved(Result<PathBuf, String>),
+    LoadSnapshot,
+    SnapshotLoaded(Result<Box<UrlAnalyzer>, String>),
+    TabChanged(Tab),
+    Batch(batch::Message),
+    Network(network::Message),
+}
+
+impl Message {
+    /// Whether the message shows up in the message log.
+    ///
+    /// `Progress` arrives several times a second while downloading and only
+    /// matters until the `Result` that follows it, so it is left out.
+    fn is_logged(&self) -> bool {
+        !matches!(
+            self,
+            Message::BodyEdited(_)
+                | Message::Progress(_)
+                | Message::LogEntrySelected(_)
+                | Message::ReplayFrom(_)
+                | Message::LogCapacityChanged(_)
+                | Message::LogKindToggled(_)
+                | Message::LogPathChanged(_)
+                | Message::ExportLog
+                | Message::LogExported(_)
+                | Message::RonEdited(_)
+                | Message::SnapshotPathChanged(_)
+                | Message::SaveSnapshot
+                | Message::SnapshotSaved(_)
+                | Message::LoadSnapshot
+                | Message::TabChanged(_)
+                | Message::Batch(_)
+                | Message::Network(_)
+                | Message::Socket(websocket::Event::Connected(_))
+                | Message::SocketDraftChanged(_)
+                | Message::StartEchoServer
+                | Message::StopEchoServer
+                | Message::EchoServerStopped(_)
+        )
+    }
+
+    /// Whether the message opens, closes or writes to the WebSocket, given
+    /// the model it resulted in.
+    fn touches_socket(&self, model: &UrlAnalyzer) -> bool {
+        match self {
+            Message::Action => model.mode == Mode::WebSocket,
+            Message::SocketSend(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether `next` replaces this message in the log instead of being added,
+    /// so typing in a field logs one entry rather than one per keystroke.
+    fn coalesces_with(&self, next: &Message) -> bool {
+        match (self, next) {
+            (Message::UrlChanged(_), Message::UrlChanged(_))
+            | (Message::BodyChanged(_), Message::BodyChanged(_))
+            | (Message::RunsChanged(_), Message::RunsChanged(_))
+            | (Message::StateEdited(_), Message::StateEdited(_)) => true,
+            (Message::HeaderNameChanged(a, _), Message::HeaderNameChanged(b, _))
+            | (Message::HeaderValueChanged(a, _), Message::HeaderValueChanged(b, _)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::MethodChanged(method) => write!(f, "MethodChanged({method})"),
+            Message::UrlChanged(url) => write!(f, "UrlChanged({url:?})"),
+            Message::HeaderAdded => write!(f, "HeaderAdded"),
+            Message::HeaderRemoved(index) => write!(f, "HeaderRemoved({index})"),
+            Message::HeaderNameChanged(index, name) => {
+                write!(f, "HeaderNameChanged({index}, {name:?})")
+            }
+            Message::HeaderValueChanged(index, value) => {
+                write!(f, "HeaderValueChanged({index}, {value:?})")
+            }
+            Message::BodyEdited(_) => write!(f, "BodyEdited"),
+            Message::BodyChanged(body) => write!(f, "BodyChanged({body:?})"),
+            Message::SecureChanged(secure) => write!(f, "SecureChanged({secure})"),
+            Message::UseCacheChanged(use_cache) => write!(f, "UseCacheChanged({use_cache})"),
+            Message::ModeChanged(mode) => write!(f, "ModeChanged({mode})"),
+            Message::Action => write!(f, "Action"),
+            Message::Progress(progress) => write!(f, "Progress({})", progress.received),
+            Message::Result(fetched) => match fetched.cache {
+                Some(cache) => write!(f, "Result({:?}, {cache})", fetched.result),
+                None => write!(f, "Result({:?})", fetched.result),
+            },
+            Message::RunsChanged(runs) => write!(f, "RunsChanged({runs})"),
+            Message::Timings(result) => {
+                write!(f, "Timings({:?})", result.as_ref().map(Timings::total))
+            }
+            Message::Compared(result) => {
+                write!(f, "Compared({:?})", result.as_ref().map(|c| c.warm))
+            }
+            Message::Inspected(result) => write!(
+                f,
+                "Inspected({:?})",
+                result.as_ref().map(|inspection| inspection.verdict.allowed)
+            ),
+            Message::SitemapUrlSelected(url) => write!(f, "SitemapUrlSelected({url:?})"),
+            Message::Socket(websocket::Event::Connected(_)) => write!(f, "SocketConnected"),
+            Message::Socket(websocket::Event::Received(text)) => {
+                write!(f, "SocketReceived({text:?})")
+            }
+            Message::Socket(websocket::Event::Disconnected(reason)) => {
+                write!(f, "SocketDisconnected({reason:?})")
+            }
+            Message::SocketDraftChanged(draft) => write!(f, "SocketDraftChanged({draft:?})"),
+            Message::SocketSend(text) => write!(f, "SocketSend({text:?})"),
+            Message::StartEchoServer => write!(f, "StartEchoServer"),
+            Message::StopEchoServer => write!(f, "StopEchoServer"),
+            Message::EchoServerStopped(result) => write!(f, "EchoServerStopped({result:?})"),
+            Message::LogEntrySelected(index) => write!(f, "LogEntrySelected({index})"),
+            Message::ReplayFrom(index) => write!(f, "ReplayFrom({index})"),
+            Message::LogCapacityChanged(capacity) => write!(f, "LogCapacityChanged({capacity})"),
+            Message::LogKindToggled(kind) => write!(f, "LogKindToggled({kind:?})"),
+            Message::LogPathChanged(path) => write!(f, "LogPathChanged({path:?})"),
+            Message::ExportLog => write!(f, "ExportLog"),
+            Message::LogExported(result) => write!(f, "LogExported({result:?})"),
+            Message::RonEdited(_) => write!(f, "RonEdited"),
+            Message::StateEdited(_) => write!(f, "StateEdited(..)"),
+            Message::SnapshotPathChanged(path) => write!(f, "SnapshotPathChanged({path:?})"),
+            Message::SaveSnapshot => write!(f, "SaveSnapshot"),
+            Message::SnapshotSaved(result) => write!(f, "SnapshotSaved({result:?})"),
+            Message::LoadSnapshot => write!(f, "LoadSnapshot"),
+            Message::SnapshotLoaded(result) => {
+                write!(f, "SnapshotLoaded({:?})", result.as_ref().map(|_| ".."))
+            }
+            Message::TabChanged(tab) => write!(f, "TabChanged({tab})"),
+            Message::Batch(message) => write!(f, "Batch({message:?})"),
+            Message::Network(message) => write!(f, "Network({message:?})"),
+        }
+    }
 }
 
 pub enum Action {
@@ -240,59 +1463,315 @@ pub enum Action {
     Run(Task<Message>),
 }
 
-/// Fetch a URL and return a result string based on the chosen mode.
-pub async fn fetch_url(url: String, secure: bool, mode: Mode) -> String {
+static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Prefix the URL with a scheme unless it already carries one.
+fn normalize_url(url: String, secure: bool) -> String {
     let protocol = if secure { "https" } else { "http" };
-    let full_url = if url.starts_with("http://") || url.starts_with("https://") {
+    let schemes = ["http://", "https://", "ws://", "wss://"];
+    if schemes.iter().any(|scheme| url.starts_with(scheme)) {
         url
     } else {
         format!("{protocol}://{url}")
-    };
+    }
+}
 
-    let start = std::time::Instant::now();
+/// Parse the URL as it will be requested, with the scheme from `secure`
+/// unless the input names one.
+pub fn validate_url(url: &str, secure: bool) -> Result<url::Url, String> {
+    if url.trim().is_empty() {
+        return Err("Please enter a URL".to_string());
+    }
+    let parsed = url::Url::parse(&normalize_url(url.trim().to_string(), secure))
+        .map_err(|e| format!("Invalid URL: {e}"))?;
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err("Invalid URL: missing host".to_string());
+    }
+    Ok(parsed)
+}
 
-    static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+/// The host in Unicode if the URL carries an internationalized (punycode) domain.
+pub fn unicode_host(url: &url::Url) -> Option<String> {
+    let host = url.domain()?;
+    let (unicode, result) = idna::domain_to_unicode(host);
+    (result.is_ok() && unicode != host).then_some(unicode)
+}
 
-    let client = match reqwest::Client::builder()
-        .user_agent(APP_USER_AGENT)
+/// Build a client with Page Boop's user agent and the given network settings.
+///
+/// Clients keep connections alive, so share one rather than building one per request.
+pub async fn build_client(network: &NetworkSettings) -> Result<reqwest::Client, String> {
+    network
+        .apply(reqwest::Client::builder().user_agent(APP_USER_AGENT))
+        .await?
         .build()
-    {
-        Ok(c) => c,
-        Err(e) => return format!("Error creating client: {e}"),
-    };
+        .map_err(|e| format!("Error creating client: {e}"))
+}
 
-    let response = match client.get(&full_url).send().await {
-        Ok(resp) => resp,
-        Err(e) => return format!("Error: {}", e),
-    };
+/// Time and size of a complete download.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub elapsed: Duration,
+    pub size: usize,
+}
+
+/// Download a URL completely, measuring how long the whole body took.
+pub async fn measure_url(
+    client: reqwest::Client,
+    url: String,
+    secure: bool,
+) -> Result<Measurement, String> {
+    let full_url = normalize_url(url, secure);
 
-    let elapsed = start.elapsed();
+    let start = Instant::now();
 
-    match mode {
-        Mode::Title => {
-            let body = match response.text().await {
-                Ok(text) => text,
-                Err(e) => return format!("Error reading body: {}", e),
-            };
+    let response = client
+        .get(&full_url)
+        .send()
+        .await
+        .map_err(|e| format!("Error: {e}"))?;
+    let bytes = response.bytes().await.map_err(|e| format!("Error: {e}"))?;
+
+    Ok(Measurement {
+        elapsed: start.elapsed(),
+        size: bytes.len(),
+    })
+}
+
+/// How far a streaming download has come.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub received: u64,
+    /// From `Content-Length`, if the server sent one.
+    pub total: Option<u64>,
+    /// Bytes per second since the previous report.
+    pub throughput: f64,
+}
+
+impl Progress {
+    pub fn percent(&self) -> Option<f32> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| self.received as f32 / total as f32 * 100.0)
+    }
+}
+
+/// Minimum time between two [`Progress`] reports.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
 
-            if let Some(start_idx) = body.find("<title>")
-                && let Some(end_idx) = body.find("</title>")
-            {
-                let title = &body[start_idx + 7..end_idx];
-                return format!("Title: {}", title.trim());
+/// Outcome of [`fetch_url`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fetched {
+    /// Human-readable result, or what went wrong.
+    pub result: Result<String, String>,
+    /// `None` if the cache was bypassed or does not apply to the request.
+    pub cache: Option<CacheStatus>,
+    /// Only produced in [`Mode::Outline`].
+    pub outline: Option<Outline>,
+}
+
+/// Fetch a URL and return a result string based on the chosen mode.
+///
+/// [`Progress`] is reported while the body downloads. With `use_cache`,
+/// plain GETs go through the on-disk [`cache`].
+pub fn fetch_url(
+    client: reqwest::Client,
+    url: url::Url,
+    mode: Mode,
+    options: RequestOptions,
+    use_cache: bool,
+) -> impl Sipper<Fetched, Progress> {
+    sipper(move |mut progress| async move {
+        let start = Instant::now();
+
+        let downloaded = download(&client, url.as_str(), &options, use_cache, &mut progress).await;
+        let (body, cache) = match downloaded {
+            Ok(downloaded) => downloaded,
+            Err(error) => {
+                return Fetched {
+                    result: Err(error),
+                    cache: None,
+                    outline: None,
+                };
             }
-            "No <title> found".to_string()
-        }
-        Mode::DownloadTime => match response.bytes().await {
-            Ok(_) => format!("Download time: {elapsed:.2?}"),
-            Err(error) => format!("Error: {error}"),
-        },
-        Mode::DownloadSize => match response.bytes().await {
-            Ok(bytes) => format!(
+        };
+
+        let mut outline = None;
+        let result = match mode {
+            Mode::Title => {
+                let body = String::from_utf8_lossy(&body);
+
+                if let Some(start_idx) = body.find("<title>")
+                    && let Some(end_idx) = body.find("</title>")
+                {
+                    let title = &body[start_idx + 7..end_idx];
+                    format!("Title: {}", title.trim())
+                } else {
+                    "No <title> found".to_string()
+                }
+            }
+            // These are handled separately in the UI; a plain fetch reports the total.
+            Mode::DownloadTime | Mode::Compare | Mode::WebSocket | Mode::Robots => {
+                format!("Download time: {:.2?}", start.elapsed())
+            }
+            Mode::DownloadSize => format!(
                 "Size: {}",
-                humansize::format_size(bytes.len(), humansize::DECIMAL)
+                humansize::format_size(body.len(), humansize::DECIMAL)
             ),
-            Err(error) => format!("Error: {error}"),
-        },
+            Mode::Outline => {
+                let extracted = outline::outline(&String::from_utf8_lossy(&body));
+                let result = format!(
+                    "Outline: {} headings, {} words, {} images",
+                    extracted.headings.len(),
+                    extracted.words,
+                    extracted.images
+                );
+                outline = Some(extracted);
+                result
+            }
+        };
+        Fetched {
+            result: Ok(result),
+            cache,
+            outline,
+        }
+    })
+}
+
+/// Get the response body, from the cache if allowed and possible.
+async fn download(
+    client: &reqwest::Client,
+    full_url: &str,
+    options: &RequestOptions,
+    use_cache: bool,
+    progress: &mut Sender<Progress>,
+) -> Result<(Vec<u8>, Option<CacheStatus>), String> {
+    let cacheable = use_cache && options.method == Method::Get;
+    let key = cache::Key::new(full_url, options);
+    let cached = if cacheable {
+        cache::lookup(&key).await
+    } else {
+        None
+    };
+
+    if let Some(entry) = &cached
+        && entry.is_fresh()
+    {
+        return Ok((entry.body.clone(), Some(CacheStatus::Hit)));
+    }
+
+    let mut request = options.build(client, full_url);
+    if let Some(entry) = &cached {
+        request = entry.conditional(request);
+    }
+    let response = request.send().await.map_err(|e| format!("Error: {}", e))?;
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED
+        && let Some(mut entry) = cached
+    {
+        if entry.refresh(response.headers()) {
+            cache::store(&entry).await;
+        }
+        return Ok((entry.body, Some(CacheStatus::Revalidated)));
+    }
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = read_body(response, progress).await?;
+
+    if cacheable
+        && status == reqwest::StatusCode::OK
+        && let Some(entry) = cache::Entry::from_response(key, &headers, body.clone())
+    {
+        cache::store(&entry).await;
+    }
+    Ok((body, cacheable.then_some(CacheStatus::Miss)))
+}
+
+/// Read the whole body, reporting [`Progress`] along the way.
+async fn read_body(
+    mut response: reqwest::Response,
+    progress: &mut Sender<Progress>,
+) -> Result<Vec<u8>, String> {
+    let total = response.content_length();
+    let mut body = Vec::new();
+    let mut last_report = Instant::now();
+    let mut since_report = 0;
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                body.extend_from_slice(&chunk);
+                since_report += chunk.len();
+            }
+            Ok(None) => return Ok(body),
+            Err(e) => return Err(format!("Error reading body: {}", e)),
+        }
+
+        let elapsed = last_report.elapsed();
+        if elapsed >= PROGRESS_INTERVAL {
+            progress
+                .send(Progress {
+                    received: body.len() as u64,
+                    total,
+                    throughput: since_report as f64 / elapsed.as_secs_f64(),
+                })
+                .await;
+            last_report = Instant::now();
+            since_report = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_leaves_the_socket_alone() {
+        let mut page = PageBoop::new();
+        let _ = page.update(Message::ModeChanged(Mode::WebSocket));
+        let _ = page.update(Message::UrlChanged("ws://127.0.0.1:9".to_string()));
+        let _ = page.update(Message::Action);
+        assert!(page.socket.is_some());
+        let transcript = page.model.transcript.clone();
+
+        let _ = page.update(Message::ReplayFrom(0));
+        assert!(page.socket.is_none());
+        assert_eq!(page.model.transcript, transcript);
+        assert_eq!(page.model.url, "ws://127.0.0.1:9");
+    }
+
+    #[test]
+    fn snapshots_keep_runs_in_range() {
+        let mut model = UrlAnalyzer {
+            runs: 0,
+            ..UrlAnalyzer::default()
+        };
+        assert_eq!(
+            snapshot::from_ron(&snapshot::to_ron(&model)).unwrap().runs,
+            1
+        );
+        model.runs = 255;
+        assert_eq!(
+            snapshot::from_ron(&snapshot::to_ron(&model)).unwrap().runs,
+            MAX_RUNS
+        );
+    }
+
+    #[test]
+    fn consecutive_state_edits_log_one_entry() {
+        let mut page = PageBoop::new();
+        let edited = |url: &str| {
+            Message::StateEdited(Box::new(UrlAnalyzer {
+                url: url.to_string(),
+                ..UrlAnalyzer::default()
+            }))
+        };
+        let _ = page.update(edited("https://a.example"));
+        let _ = page.update(edited("https://ab.example"));
+
+        assert!(page.message_log.get(0).is_some());
+        assert!(page.message_log.get(1).is_none());
+        assert_eq!(page.model.url, "https://ab.example");
     }
 }
@@ -1,6 +1,8 @@
-use iced::{Element, Font, Task};
+use iced::{Element, Font, Subscription, Task};
 use page_boop::{FIRA_MONO_BYTES, PageBoop};
 
+mod cli;
+
 const FIRA_MONO: Font = Font::with_name("Fira Mono");
 
 struct App {
@@ -13,7 +15,13 @@ enum Message {
 }
 
 fn main() -> iced::Result {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
     iced::application(App::default, App::update, App::view)
+        .subscription(App::subscription)
         .title("Page Boop")
         .font(FIRA_MONO_BYTES)
         .default_font(FIRA_MONO)
@@ -38,6 +46,10 @@ impl App {
         }
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        self.boop.subscription().map(Message::Boop)
+    }
+
     fn view(&self) -> Element<'_, Message> {
         iced::widget::container(self.boop.view().map(Message::Boop))
             .padding(30)
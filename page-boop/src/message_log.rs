@@ -0,0 +1,162 @@
+//! Bounded log of messages together with the model each one produced.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::{Message, UrlAnalyzer};
+
+pub const DEFAULT_CAPACITY: usize = 200;
+pub const MAX_CAPACITY: usize = 1000;
+pub const DEFAULT_EXPORT_PATH: &str = "page-boop-log.txt";
+
+pub struct Entry {
+    pub message: Message,
+    pub model: UrlAnalyzer,
+    /// Time since the log was created.
+    pub at: Duration,
+    /// The message name without its payload, e.g. `UrlChanged`.
+    pub kind: String,
+}
+
+/// Ring buffer of [`Entry`]s; the oldest entries are dropped once full.
+pub struct MessageLog {
+    entries: VecDeque<Entry>,
+    /// The model before the oldest entry, where replaying everything starts.
+    base: UrlAnalyzer,
+    capacity: usize,
+    hidden: BTreeSet<String>,
+    started: Instant,
+}
+
+impl Default for MessageLog {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            base: UrlAnalyzer::default(),
+            capacity: DEFAULT_CAPACITY,
+            hidden: BTreeSet::new(),
+            started: Instant::now(),
+        }
+    }
+}
+
+impl MessageLog {
+    /// Record a message, merging it into the last entry if both are typing
+    /// in the same field.
+    pub fn push(&mut self, message: Message, model: UrlAnalyzer) {
+        let entry = Entry {
+            kind: kind(&message),
+            at: self.started.elapsed(),
+            message,
+            model,
+        };
+        match self.entries.back_mut() {
+            Some(last) if last.message.coalesces_with(&entry.message) => *last = entry,
+            _ => {
+                self.entries.push_back(entry);
+                self.trim();
+            }
+        }
+    }
+
+    fn trim(&mut self) {
+        while self.entries.len() > self.capacity {
+            if let Some(oldest) = self.entries.pop_front() {
+                self.base = oldest.model;
+            }
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.clamp(1, MAX_CAPACITY);
+        self.trim();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Entry> {
+        self.entries.get(index)
+    }
+
+    /// The model right before the entry at `index`.
+    pub fn before(&self, index: usize) -> UrlAnalyzer {
+        index
+            .checked_sub(1)
+            .and_then(|previous| self.entries.get(previous))
+            .map_or_else(|| self.base.clone(), |entry| entry.model.clone())
+    }
+
+    /// Drop every entry after the one at `index`.
+    pub fn truncate_after(&mut self, index: usize) {
+        self.entries.truncate(index + 1);
+    }
+
+    /// Remove the entry at `index` and everything after it.
+    pub fn drain_from(&mut self, index: usize) -> Vec<Entry> {
+        let index = index.min(self.entries.len());
+        self.entries.drain(index..).collect()
+    }
+
+    /// Entries not hidden by the filter, with their index.
+    pub fn visible(&self) -> impl Iterator<Item = (usize, &Entry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !self.hidden.contains(&entry.kind))
+    }
+
+    /// Every kind in the log or hidden by the filter, sorted.
+    pub fn kinds(&self) -> BTreeSet<&str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.kind.as_str())
+            .chain(self.hidden.iter().map(String::as_str))
+            .collect()
+    }
+
+    pub fn is_hidden(&self, kind: &str) -> bool {
+        self.hidden.contains(kind)
+    }
+
+    pub fn toggle_kind(&mut self, kind: String) {
+        if !self.hidden.remove(&kind) {
+            self.hidden.insert(kind);
+        }
+    }
+
+    /// Visible entries as text, one per line.
+    pub fn to_text(&self) -> String {
+        self.visible()
+            .map(|(_, entry)| format!("{}\t{}\n", timestamp(entry.at), entry.message))
+            .collect()
+    }
+}
+
+/// Time since the log started, e.g. `+12.345s`.
+pub fn timestamp(at: Duration) -> String {
+    format!("+{:.3}s", at.as_secs_f64())
+}
+
+/// The message name as shown in the log, without its payload.
+fn kind(message: &Message) -> String {
+    let shown = message.to_string();
+    match shown.split_once('(') {
+        Some((kind, _)) => kind.to_string(),
+        None => shown,
+    }
+}
+
+/// Write exported log text, returning the path written to.
+pub async fn export(path: PathBuf, text: String) -> Result<PathBuf, String> {
+    tokio::fs::write(&path, text)
+        .await
+        .map_err(|e| format!("Error exporting {}: {e}", path.display()))?;
+    Ok(path)
+}
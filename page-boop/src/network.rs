@@ -0,0 +1,342 @@
+//! Proxy and TLS trust settings applied to every connection Page Boop makes.
+
+use std::sync::Arc;
+
+use hyper_util::client::proxy::matcher::Matcher;
+use iced::{
+    Element,
+    widget::{checkbox, column, container, row, space, text, text_input},
+};
+use tokio::sync::OnceCell;
+use tokio_rustls::rustls::{
+    self, ClientConfig, DigitallySignedStruct, SignatureScheme,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::CryptoProvider,
+    pki_types::{CertificateDer, ServerName, UnixTime, pem::PemObject},
+};
+
+use crate::StyleConfig;
+use crate::tunnel::Route;
+
+/// Environment variables for each proxy setting, uppercase first.
+const ALL_PROXY_VARS: [&str; 2] = ["ALL_PROXY", "all_proxy"];
+const HTTP_PROXY_VARS: [&str; 2] = ["HTTP_PROXY", "http_proxy"];
+const HTTPS_PROXY_VARS: [&str; 2] = ["HTTPS_PROXY", "https_proxy"];
+const NO_PROXY_VARS: [&str; 2] = ["NO_PROXY", "no_proxy"];
+
+/// PEM file with extra root certificates.
+const CA_FILE_VAR: &str = "PAGE_BOOP_CA_FILE";
+
+/// Set to `1` to skip certificate verification.
+const INSECURE_VAR: &str = "PAGE_BOOP_INSECURE";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NetworkSettings {
+    /// `http://`, `https://` or `socks5://` proxy URL used for both schemes
+    /// unless one has its own; empty for a direct connection.
+    pub proxy: String,
+    /// Proxy for `http` URLs only.
+    pub http_proxy: String,
+    /// Proxy for `https` URLs only.
+    pub https_proxy: String,
+    /// Hosts reached directly, comma-separated as in `NO_PROXY`.
+    pub no_proxy: String,
+    /// Path to a PEM bundle trusted in addition to the system roots.
+    pub ca_file: String,
+    pub accept_invalid_certs: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ProxyChanged(String),
+    HttpProxyChanged(String),
+    HttpsProxyChanged(String),
+    NoProxyChanged(String),
+    CaFileChanged(String),
+    AcceptInvalidCertsChanged(bool),
+}
+
+impl NetworkSettings {
+    /// Settings taken from the usual proxy variables, `PAGE_BOOP_CA_FILE`
+    /// and `PAGE_BOOP_INSECURE`.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let first = |names: [&str; 2]| names.iter().find_map(|name| var(name)).unwrap_or_default();
+        Self {
+            proxy: first(ALL_PROXY_VARS),
+            http_proxy: first(HTTP_PROXY_VARS),
+            https_proxy: first(HTTPS_PROXY_VARS),
+            no_proxy: first(NO_PROXY_VARS),
+            ca_file: var(CA_FILE_VAR).unwrap_or_default(),
+            accept_invalid_certs: var(INSECURE_VAR).is_some_and(|v| v == "1"),
+        }
+    }
+
+    /// Configure proxy and certificate handling on a client builder.
+    pub async fn apply(
+        &self,
+        builder: reqwest::ClientBuilder,
+    ) -> Result<reqwest::ClientBuilder, String> {
+        let (all, http, https) = (
+            self.proxy.trim(),
+            self.http_proxy.trim(),
+            self.https_proxy.trim(),
+        );
+        let no_proxy = reqwest::NoProxy::from_string(&self.no_proxy);
+
+        // Only the configured proxies: empty ones mean a direct connection,
+        // even if one is set in the environment. Scheme-specific proxies come
+        // first so they win over `proxy`.
+        let mut builder = builder.no_proxy();
+        for proxy in [
+            (!http.is_empty()).then(|| reqwest::Proxy::http(http)),
+            (!https.is_empty()).then(|| reqwest::Proxy::https(https)),
+            (!all.is_empty()).then(|| reqwest::Proxy::all(all)),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            let proxy = proxy.map_err(|e| format!("Invalid proxy: {e}"))?;
+            builder = builder.proxy(proxy.no_proxy(no_proxy.clone()));
+        }
+
+        let certs = self
+            .extra_roots()
+            .await?
+            .iter()
+            .map(|cert| reqwest::Certificate::from_der(cert))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid certificate in {}: {e}", self.ca_file.trim()))?;
+        Ok(builder
+            .tls_certs_merge(certs)
+            .tls_danger_accept_invalid_certs(self.accept_invalid_certs))
+    }
+
+    /// TLS configuration for connections made without reqwest, trusting the
+    /// same certificates as the clients do.
+    pub async fn tls_config(&self) -> Result<ClientConfig, String> {
+        let builder = ClientConfig::builder();
+        let provider = builder.crypto_provider().clone();
+        let verifier: Arc<dyn ServerCertVerifier> = if self.accept_invalid_certs {
+            Arc::new(AcceptAnyCert(provider))
+        } else {
+            Arc::new(
+                rustls_platform_verifier::Verifier::new_with_extra_roots(
+                    self.extra_roots().await?,
+                    provider,
+                )
+                .map_err(|e| format!("Error creating TLS config: {e}"))?,
+            )
+        };
+        Ok(builder
+            .dangerous()
+            .with_custom_certificate_verifier(verifier)
+            .with_no_client_auth())
+    }
+
+    /// The certificates in `ca_file`; none if it isn't set.
+    async fn extra_roots(&self) -> Result<Vec<CertificateDer<'static>>, String> {
+        let ca_file = self.ca_file.trim();
+        if ca_file.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pem = tokio::fs::read(ca_file)
+            .await
+            .map_err(|e| format!("Error reading {ca_file}: {e}"))?;
+        let certs = CertificateDer::pem_slice_iter(&pem)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid certificates in {ca_file}: {e}"))?;
+        if certs.is_empty() {
+            return Err(format!("No certificates found in {ca_file}"));
+        }
+        Ok(certs)
+    }
+
+    /// How to reach `url`, through the proxy the settings pick for it.
+    ///
+    /// `ws` and `wss` URLs use the `http` and `https` proxies.
+    pub fn route(&self, url: &url::Url) -> Result<Route, String> {
+        let scheme = match url.scheme() {
+            "http" | "ws" => "http",
+            "https" | "wss" => "https",
+            _ => return Ok(Route::Direct),
+        };
+        let host = url.host_str().unwrap_or_default();
+        let uri: http::Uri = match url.port() {
+            Some(port) => format!("{scheme}://{host}:{port}/"),
+            None => format!("{scheme}://{host}/"),
+        }
+        .parse()
+        .map_err(|e| format!("Invalid URL: {e}"))?;
+
+        let matcher = Matcher::builder()
+            .all(self.proxy.trim())
+            .http(self.http_proxy.trim())
+            .https(self.https_proxy.trim())
+            .no(self.no_proxy.trim())
+            .build();
+        let Some(proxy) = matcher.intercept(&uri) else {
+            return Ok(Route::Direct);
+        };
+
+        let proxy_uri = proxy.uri();
+        let proxy_host = proxy_uri
+            .host()
+            .unwrap_or_default()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        match proxy_uri.scheme_str() {
+            Some("http") => Ok(Route::Http {
+                host: proxy_host,
+                port: proxy_uri.port_u16().unwrap_or(80),
+                auth: proxy.basic_auth().cloned(),
+            }),
+            Some(socks @ ("socks5" | "socks5h")) => Ok(Route::Socks5 {
+                host: proxy_host,
+                port: proxy_uri.port_u16().unwrap_or(1080),
+                auth: proxy
+                    .raw_auth()
+                    .map(|(user, password)| (user.to_string(), password.to_string())),
+                remote_dns: socks == "socks5h",
+            }),
+            _ => Err(format!(
+                "Unsupported proxy {proxy_uri} for this mode: use http:// or socks5://"
+            )),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::ProxyChanged(proxy) => self.proxy = proxy,
+            Message::HttpProxyChanged(proxy) => self.http_proxy = proxy,
+            Message::HttpsProxyChanged(proxy) => self.https_proxy = proxy,
+            Message::NoProxyChanged(no_proxy) => self.no_proxy = no_proxy,
+            Message::CaFileChanged(ca_file) => self.ca_file = ca_file,
+            Message::AcceptInvalidCertsChanged(accept) => self.accept_invalid_certs = accept,
+        }
+    }
+
+    pub fn view<'a>(&'a self, style: &StyleConfig) -> Element<'a, Message> {
+        let mono = style.mono_font;
+        let subtitle = style.subtitle_color;
+
+        let warning: Element<'a, Message> = if self.accept_invalid_certs {
+            container(text(
+                "Certificate verification is off: any server can impersonate any site.",
+            ))
+            .padding([6, 12])
+            .style(container::danger)
+            .into()
+        } else {
+            space().into()
+        };
+
+        column![
+            text("Proxy").color(subtitle),
+            text_input("socks5://127.0.0.1:1080 (empty: direct)", &self.proxy)
+                .on_input(Message::ProxyChanged)
+                .font(mono),
+            text("HTTP and HTTPS proxies, if they differ").color(subtitle),
+            row![
+                text_input("http:// URLs", &self.http_proxy)
+                    .on_input(Message::HttpProxyChanged)
+                    .font(mono),
+                text_input("https:// URLs", &self.https_proxy)
+                    .on_input(Message::HttpsProxyChanged)
+                    .font(mono),
+            ]
+            .spacing(12),
+            text(
+                "HTTP proxies forward plain http:// requests; https://, ws:// and wss:// \
+                 need them to allow CONNECT tunnels to the target's port.",
+            )
+            .color(subtitle),
+            text("No proxy for").color(subtitle),
+            text_input("localhost, .internal.example, 10.0.0.0/8", &self.no_proxy)
+                .on_input(Message::NoProxyChanged)
+                .font(mono),
+            text("Extra root certificates (PEM file)").color(subtitle),
+            text_input("/path/to/corporate-ca.pem", &self.ca_file)
+                .on_input(Message::CaFileChanged)
+                .font(mono),
+            checkbox(self.accept_invalid_certs)
+                .label("Accept invalid certificates")
+                .on_toggle(Message::AcceptInvalidCertsChanged),
+            warning,
+        ]
+        .spacing(12)
+        .width(iced::Fill)
+        .into()
+    }
+}
+
+/// The client built from the settings on first use, off the UI thread, and
+/// shared by every request until the settings change.
+#[derive(Debug, Clone, Default)]
+pub struct SharedClient(Arc<OnceCell<Result<reqwest::Client, String>>>);
+
+impl SharedClient {
+    pub fn get(
+        &self,
+        settings: &NetworkSettings,
+    ) -> impl Future<Output = Result<reqwest::Client, String>> + use<> {
+        let cell = self.0.clone();
+        let settings = settings.clone();
+        async move {
+            cell.get_or_init(|| crate::build_client(&settings))
+                .await
+                .clone()
+        }
+    }
+}
+
+/// Accepts every certificate, for `accept_invalid_certs`. Handshake
+/// signatures are still checked, as reqwest does.
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
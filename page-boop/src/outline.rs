@@ -0,0 +1,155 @@
+//! Plain-text outline of an HTML page: headings, counts and a readable preview.
+//!
+//! This is a forgiving tag scanner, not an HTML parser; it only needs to be
+//! good enough to show the shape of a page.
+
+use serde::Serialize;
+
+/// Characters of extracted text kept for the preview.
+const PREVIEW_LEN: usize = 4000;
+
+/// Elements whose contents are never shown.
+const HIDDEN: [&str; 5] = ["script", "style", "noscript", "template", "title"];
+
+/// Elements that start a new line in the text preview.
+const BLOCKS: [&str; 14] = [
+    "p",
+    "div",
+    "br",
+    "li",
+    "tr",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "nav",
+    "main",
+    "blockquote",
+    "pre",
+    "table",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Heading {
+    /// 1 for `<h1>` through 6 for `<h6>`.
+    pub level: u8,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct Outline {
+    pub headings: Vec<Heading>,
+    pub words: usize,
+    pub images: usize,
+    pub preview: String,
+}
+
+/// Extract the outline of an HTML document.
+pub fn outline(html: &str) -> Outline {
+    // ASCII lowercasing keeps byte offsets, so both strings share indices.
+    let lower = html.to_ascii_lowercase();
+
+    let mut outline = Outline::default();
+    let mut text = String::new();
+    let mut heading: Option<Heading> = None;
+
+    let mut pos = 0;
+    while let Some(offset) = lower[pos..].find('<') {
+        let lt = pos + offset;
+        push_text(&html[pos..lt], &mut text, heading.as_mut());
+
+        if lower[lt..].starts_with("<!--") {
+            pos = lower[lt..]
+                .find("-->")
+                .map_or(html.len(), |end| lt + end + 3);
+            continue;
+        }
+        let Some(gt) = lower[lt..].find('>').map(|end| lt + end) else {
+            pos = html.len();
+            break;
+        };
+        pos = gt + 1;
+
+        let tag = &lower[lt + 1..gt];
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+
+        if !closing && HIDDEN.contains(&name) {
+            let end = format!("</{name}");
+            pos = lower[pos..].find(&end).map_or(html.len(), |end| pos + end);
+            continue;
+        }
+
+        if name == "img" {
+            outline.images += 1;
+        } else if let Some(level) = heading_level(name) {
+            text.push('\n');
+            if closing {
+                if let Some(mut done) = heading.take() {
+                    done.text = collapse(&done.text);
+                    if !done.text.is_empty() {
+                        outline.headings.push(done);
+                    }
+                }
+            } else {
+                heading = Some(Heading {
+                    level,
+                    text: String::new(),
+                });
+            }
+        } else if BLOCKS.contains(&name) {
+            text.push('\n');
+        }
+    }
+    push_text(&html[pos.min(html.len())..], &mut text, heading.as_mut());
+
+    outline.words = text.split_whitespace().count();
+    outline.preview = text
+        .lines()
+        .map(collapse)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .chars()
+        .take(PREVIEW_LEN)
+        .collect();
+    outline
+}
+
+fn heading_level(name: &str) -> Option<u8> {
+    match name.as_bytes() {
+        [b'h', level @ b'1'..=b'6'] => Some(level - b'0'),
+        _ => None,
+    }
+}
+
+fn push_text(raw: &str, text: &mut String, heading: Option<&mut Heading>) {
+    let decoded = decode_entities(raw);
+    if let Some(heading) = heading {
+        heading.text.push_str(&decoded);
+    }
+    text.push_str(&decoded);
+}
+
+/// Collapse runs of whitespace into single spaces.
+fn collapse(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decode the handful of entities common in running text.
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    s.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumIter, Serialize, Deserialize)]
+pub enum Method {
+    #[default]
+    #[strum(serialize = "GET")]
+    Get,
+
+    #[strum(serialize = "HEAD")]
+    Head,
+
+    #[strum(serialize = "POST")]
+    Post,
+
+    #[strum(serialize = "PUT")]
+    Put,
+
+    #[strum(serialize = "DELETE")]
+    Delete,
+}
+
+impl Method {
+    /// Whether the body editor is offered for this method.
+    pub fn has_body(self) -> bool {
+        matches!(self, Method::Post | Method::Put)
+    }
+
+    /// Whether sending the request more than once is harmless.
+    pub fn is_safe(self) -> bool {
+        matches!(self, Method::Get | Method::Head)
+    }
+
+    pub fn as_reqwest(self) -> reqwest::Method {
+        match self {
+            Method::Get => reqwest::Method::GET,
+            Method::Head => reqwest::Method::HEAD,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Delete => reqwest::Method::DELETE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Header {
+    pub name: String,
+    pub value: String,
+}
+
+/// Everything about a request besides its URL.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RequestOptions {
+    pub method: Method,
+    pub headers: Vec<Header>,
+    pub body: String,
+}
+
+impl RequestOptions {
+    /// Headers with a name; rows still being filled in are skipped.
+    pub fn headers(&self) -> impl Iterator<Item = &Header> {
+        self.headers.iter().filter(|h| !h.name.trim().is_empty())
+    }
+
+    /// Reject header names and values that aren't valid HTTP, such as ones
+    /// containing line breaks.
+    pub fn validate_headers(&self) -> Result<(), String> {
+        for header in self.headers() {
+            reqwest::header::HeaderName::from_bytes(header.name.trim().as_bytes())
+                .map_err(|_| format!("Invalid header name: {:?}", header.name.trim()))?;
+            reqwest::header::HeaderValue::from_str(&header.value)
+                .map_err(|_| format!("Invalid value for header {}", header.name.trim()))?;
+        }
+        Ok(())
+    }
+
+    /// The body to send, if the method carries one and it isn't empty.
+    pub fn body(&self) -> Option<&str> {
+        Some(self.body.as_str()).filter(|body| self.method.has_body() && !body.is_empty())
+    }
+
+    /// Apply method, headers and body to a request for `url`.
+    pub fn build(&self, client: &reqwest::Client, url: &str) -> reqwest::RequestBuilder {
+        let mut request = client.request(self.method.as_reqwest(), url);
+        for header in self.headers() {
+            request = request.header(header.name.trim(), &header.value);
+        }
+        if let Some(body) = self.body() {
+            request = request.body(body.to_string());
+        }
+        request
+    }
+}
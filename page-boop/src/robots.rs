@@ -0,0 +1,296 @@
+//! robots.txt rules and sitemap listing for a host.
+//!
+//! Matching follows RFC 9309: the group for our product token wins over `*`,
+//! the longest matching pattern decides, and `Allow` wins ties.
+
+use std::fmt;
+
+use crate::APP_USER_AGENT;
+
+/// Sitemap entries kept; large sites list tens of thousands.
+const MAX_SITEMAP_URLS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub allow: bool,
+    pub pattern: String,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.allow { "Allow" } else { "Disallow" };
+        write!(f, "{kind}: {}", self.pattern)
+    }
+}
+
+/// Rules shared by one or more consecutive `User-agent` lines.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Group {
+    pub agents: Vec<String>,
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Robots {
+    pub groups: Vec<Group>,
+    pub sitemaps: Vec<String>,
+}
+
+/// Whether a path may be crawled, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verdict {
+    pub allowed: bool,
+    /// The user agent of the group that applied, `None` if no group did.
+    pub agent: Option<String>,
+    /// The deciding rule; `None` means nothing matched, which allows the path.
+    pub rule: Option<Rule>,
+}
+
+impl Robots {
+    pub fn parse(text: &str) -> Self {
+        let mut robots = Robots::default();
+        let mut group = Group::default();
+        // Whether the group has had a rule line, even an empty `Disallow:`.
+        let mut has_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    // A user agent after rules starts the next group.
+                    if has_rules {
+                        robots.groups.push(std::mem::take(&mut group));
+                        has_rules = false;
+                    }
+                    group.agents.push(value.to_string());
+                }
+                "allow" | "disallow" if !group.agents.is_empty() => {
+                    has_rules = true;
+                    // An empty `Disallow:` allows everything, like no rule at all.
+                    if !value.is_empty() {
+                        group.rules.push(Rule {
+                            allow: key.trim().eq_ignore_ascii_case("allow"),
+                            pattern: value.to_string(),
+                        });
+                    }
+                }
+                "sitemap" => robots.sitemaps.push(value.to_string()),
+                _ => {}
+            }
+        }
+        if !group.agents.is_empty() {
+            robots.groups.push(group);
+        }
+        robots
+    }
+
+    /// Decide whether `agent` (a product token such as `page-boop`) may fetch `path`.
+    pub fn check(&self, agent: &str, path: &str) -> Verdict {
+        let matching = |wanted: &dyn Fn(&str) -> bool| -> Vec<&Group> {
+            self.groups
+                .iter()
+                .filter(|group| group.agents.iter().any(|a| wanted(a)))
+                .collect()
+        };
+
+        let mut groups = matching(&|a| a.eq_ignore_ascii_case(agent));
+        let mut applied = agent;
+        if groups.is_empty() {
+            groups = matching(&|a| a == "*");
+            applied = "*";
+        }
+        if groups.is_empty() {
+            return Verdict {
+                allowed: true,
+                agent: None,
+                rule: None,
+            };
+        }
+
+        let rule = groups
+            .iter()
+            .flat_map(|group| &group.rules)
+            .filter(|rule| matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .cloned();
+        Verdict {
+            allowed: rule.as_ref().is_none_or(|rule| rule.allow),
+            agent: Some(applied.to_string()),
+            rule,
+        }
+    }
+}
+
+/// Match a robots.txt pattern, with `*` wildcards and a `$` end anchor.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+/// The product token robots.txt groups are matched against, e.g. `page-boop`.
+pub fn product_token() -> &'static str {
+    APP_USER_AGENT.split('/').next().unwrap_or(APP_USER_AGENT)
+}
+
+/// URLs listed in a sitemap or sitemap index.
+pub fn sitemap_urls(xml: &str) -> Vec<String> {
+    xml.split("<loc>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</loc>"))
+        .map(|(loc, _)| loc.trim().replace("&amp;", "&"))
+        .take(MAX_SITEMAP_URLS)
+        .collect()
+}
+
+/// Everything [`inspect`] found out about a host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inspection {
+    pub robots_url: String,
+    /// `None` if the host has no robots.txt.
+    pub robots: Option<Robots>,
+    pub path: String,
+    pub verdict: Verdict,
+    pub sitemap_url: Option<String>,
+    pub sitemap: Result<Vec<String>, String>,
+}
+
+/// Fetch robots.txt and the first sitemap of the URL's host, and check the
+/// URL's path against the rules for [`product_token`].
+pub async fn inspect(client: reqwest::Client, url: url::Url) -> Result<Inspection, String> {
+    let robots_url = url
+        .join("/robots.txt")
+        .map_err(|e| format!("Invalid URL: {e}"))?;
+
+    let response = client
+        .get(robots_url.clone())
+        .send()
+        .await
+        .map_err(|e| format!("Error: {e}"))?;
+    let status = response.status();
+    // A missing robots.txt (any 4xx) places no restrictions.
+    let robots = if status.is_client_error() {
+        None
+    } else if status.is_success() {
+        let text = response.text().await.map_err(|e| format!("Error: {e}"))?;
+        Some(Robots::parse(&text))
+    } else {
+        return Err(format!("{robots_url} returned {status}"));
+    };
+
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    };
+    let verdict = match &robots {
+        Some(robots) => robots.check(product_token(), &path),
+        None => Verdict {
+            allowed: true,
+            agent: None,
+            rule: None,
+        },
+    };
+
+    let sitemap_url = robots
+        .as_ref()
+        .and_then(|robots| robots.sitemaps.first().cloned())
+        .or_else(|| url.join("/sitemap.xml").ok().map(String::from));
+    let sitemap = match &sitemap_url {
+        Some(sitemap_url) => fetch_sitemap(&client, sitemap_url).await,
+        None => Ok(Vec::new()),
+    };
+
+    Ok(Inspection {
+        robots_url: robots_url.to_string(),
+        robots,
+        path,
+        verdict,
+        sitemap_url,
+        sitemap,
+    })
+}
+
+async fn fetch_sitemap(client: &reqwest::Client, url: &str) -> Result<Vec<String>, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Error: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("{url} returned {}", response.status()));
+    }
+    let xml = response.text().await.map_err(|e| format!("Error: {e}"))?;
+    Ok(sitemap_urls(&xml))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_empty_disallow_ends_its_group() {
+        let robots =
+            Robots::parse("User-agent: page-boop\nDisallow:\n\nUser-agent: *\nDisallow: /\n");
+
+        assert_eq!(robots.groups.len(), 2);
+        let verdict = robots.check("page-boop", "/index.html");
+        assert!(verdict.allowed);
+        assert_eq!(verdict.agent.as_deref(), Some("page-boop"));
+        assert!(!robots.check("other-bot", "/index.html").allowed);
+    }
+
+    #[test]
+    fn patterns_match_wildcards_and_the_end_anchor() {
+        assert!(matches("/private", "/private/notes"));
+        assert!(!matches("/private", "/public"));
+        assert!(matches("/*.php", "/blog/index.php?page=2"));
+        assert!(matches("/*.php$", "/blog/index.php"));
+        assert!(!matches("/*.php$", "/blog/index.php?page=2"));
+        assert!(matches("/exact$", "/exact"));
+        assert!(!matches("/exact$", "/exactly"));
+        assert!(matches("/a*b*c", "/a-x-b-y-c-z"));
+        assert!(!matches("/a*c*b", "/a-b-c"));
+    }
+
+    #[test]
+    fn the_longest_match_wins_and_allow_wins_ties() {
+        let robots = Robots::parse(
+            "User-agent: *\n\
+             Disallow: /docs\n\
+             Allow: /docs/public\n\
+             Allow: /page\n\
+             Disallow: /page\n",
+        );
+
+        let verdict = robots.check("page-boop", "/docs/public/a.html");
+        assert!(verdict.allowed);
+        assert_eq!(
+            verdict.rule.map(|rule| rule.pattern).as_deref(),
+            Some("/docs/public")
+        );
+        assert!(!robots.check("page-boop", "/docs/private").allowed);
+        assert!(robots.check("page-boop", "/page").allowed);
+        assert!(robots.check("page-boop", "/elsewhere").rule.is_none());
+    }
+}
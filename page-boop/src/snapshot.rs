@@ -0,0 +1,35 @@
+//! Saving and loading [`UrlAnalyzer`] snapshots as RON.
+
+use std::path::PathBuf;
+
+use crate::{MAX_RUNS, UrlAnalyzer};
+
+pub const DEFAULT_PATH: &str = "page-boop.ron";
+
+pub fn to_ron(model: &UrlAnalyzer) -> String {
+    ron::ser::to_string_pretty(model, ron::ser::PrettyConfig::default())
+        .unwrap_or_else(|e| format!("Error: {e}"))
+}
+
+/// Parse a snapshot, bringing hand-edited values back into the ranges the
+/// controls offer.
+pub fn from_ron(ron: &str) -> Result<UrlAnalyzer, String> {
+    let mut model: UrlAnalyzer = ron::from_str(ron).map_err(|e| e.to_string())?;
+    model.runs = model.runs.clamp(1, MAX_RUNS);
+    Ok(model)
+}
+
+/// Write a snapshot, returning the path written to.
+pub async fn save(path: PathBuf, model: UrlAnalyzer) -> Result<PathBuf, String> {
+    tokio::fs::write(&path, to_ron(&model))
+        .await
+        .map_err(|e| format!("Error saving {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+pub async fn load(path: PathBuf) -> Result<UrlAnalyzer, String> {
+    let ron = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Error loading {}: {e}", path.display()))?;
+    from_ron(&ron)
+}
@@ -0,0 +1,374 @@
+//! Connections through HTTP and SOCKS5 proxies, for the requests Page Boop
+//! makes without reqwest: timed downloads and WebSockets.
+
+use std::net::IpAddr;
+
+use reqwest::header::HeaderValue;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Longest proxy response head read before giving up.
+const MAX_HEAD: usize = 8192;
+
+/// How a connection reaches its target.
+#[derive(Debug, Clone)]
+pub enum Route {
+    Direct,
+    /// A `CONNECT` tunnel through an HTTP proxy.
+    Http {
+        host: String,
+        port: u16,
+        auth: Option<HeaderValue>,
+    },
+    Socks5 {
+        host: String,
+        port: u16,
+        auth: Option<(String, String)>,
+        /// `socks5h`: the proxy resolves the target's name.
+        remote_dns: bool,
+    },
+}
+
+impl Route {
+    /// Where the TCP connection goes: the proxy, or the target itself.
+    pub fn first_hop<'a>(&'a self, host: &'a str, port: u16) -> (&'a str, u16) {
+        match self {
+            Route::Direct => (host, port),
+            Route::Http { host, port, .. } | Route::Socks5 { host, port, .. } => (host, *port),
+        }
+    }
+
+    /// Whether plain HTTP requests go to the proxy with an absolute URL, as
+    /// reqwest sends them, rather than through a `CONNECT` tunnel, which
+    /// many proxies only allow to port 443.
+    pub fn forwards_http(&self) -> bool {
+        matches!(self, Route::Http { .. })
+    }
+
+    /// Credentials for an HTTP proxy, sent with forwarded requests.
+    pub fn proxy_authorization(&self) -> Option<&HeaderValue> {
+        match self {
+            Route::Http { auth, .. } => auth.as_ref(),
+            Route::Direct | Route::Socks5 { .. } => None,
+        }
+    }
+
+    /// Ask the proxy on `stream` to connect it to `host:port`. Direct
+    /// connections are left as they are.
+    pub async fn tunnel(
+        &self,
+        stream: &mut TcpStream,
+        host: &str,
+        port: u16,
+    ) -> Result<(), String> {
+        match self {
+            Route::Direct => Ok(()),
+            Route::Http { auth, .. } => http_connect(stream, host, port, auth.as_ref()).await,
+            Route::Socks5 {
+                auth, remote_dns, ..
+            } => socks5_connect(stream, host, port, auth.as_ref(), *remote_dns).await,
+        }
+    }
+
+    /// Open a connection to `host:port` along this route.
+    pub async fn connect(&self, host: &str, port: u16) -> Result<TcpStream, String> {
+        let mut stream = TcpStream::connect(self.first_hop(host, port))
+            .await
+            .map_err(|e| format!("Connect error: {e}"))?;
+        self.tunnel(&mut stream, host, port).await?;
+        Ok(stream)
+    }
+}
+
+/// `host:port`, with brackets around IPv6 addresses.
+fn authority(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
+async fn http_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    auth: Option<&HeaderValue>,
+) -> Result<(), String> {
+    let authority = authority(host, port);
+    let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+    if let Some(auth) = auth.and_then(|auth| auth.to_str().ok()) {
+        request.push_str(&format!("Proxy-Authorization: {auth}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| format!("Proxy error: {e}"))?;
+
+    // Byte by byte, so nothing sent through the tunnel is read here.
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > MAX_HEAD {
+            return Err("Proxy error: response head too long".to_string());
+        }
+        head.push(
+            stream
+                .read_u8()
+                .await
+                .map_err(|e| format!("Proxy error: {e}"))?,
+        );
+    }
+    let head = String::from_utf8_lossy(&head);
+    let status = head.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(format!("Proxy refused the tunnel: {status}")),
+    }
+}
+
+async fn socks5_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    auth: Option<&(String, String)>,
+    remote_dns: bool,
+) -> Result<(), String> {
+    let io = |e: std::io::Error| format!("SOCKS error: {e}");
+
+    let method = if auth.is_some() { 0x02 } else { 0x00 };
+    stream.write_all(&[0x05, 0x01, method]).await.map_err(io)?;
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply).await.map_err(io)?;
+    if reply != [0x05, method] {
+        return Err("SOCKS proxy refused the authentication method".to_string());
+    }
+
+    if let Some((user, password)) = auth {
+        let too_long = || "SOCKS credentials are too long".to_string();
+        let mut request = vec![0x01, u8::try_from(user.len()).map_err(|_| too_long())?];
+        request.extend_from_slice(user.as_bytes());
+        request.push(u8::try_from(password.len()).map_err(|_| too_long())?);
+        request.extend_from_slice(password.as_bytes());
+        stream.write_all(&request).await.map_err(io)?;
+        stream.read_exact(&mut reply).await.map_err(io)?;
+        if reply[1] != 0x00 {
+            return Err("SOCKS proxy rejected the credentials".to_string());
+        }
+    }
+
+    let address = match host.parse::<IpAddr>() {
+        Ok(address) => Some(address),
+        Err(_) if remote_dns => None,
+        Err(_) => Some(
+            tokio::net::lookup_host((host, port))
+                .await
+                .map_err(|e| format!("DNS error: {e}"))?
+                .next()
+                .ok_or_else(|| format!("DNS error: no address for {host}"))?
+                .ip(),
+        ),
+    };
+    let mut request = vec![0x05, 0x01, 0x00];
+    match address {
+        Some(IpAddr::V4(address)) => {
+            request.push(0x01);
+            request.extend_from_slice(&address.octets());
+        }
+        Some(IpAddr::V6(address)) => {
+            request.push(0x04);
+            request.extend_from_slice(&address.octets());
+        }
+        None => {
+            request.push(0x03);
+            request.push(u8::try_from(host.len()).map_err(|_| "Host name too long".to_string())?);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await.map_err(io)?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply).await.map_err(io)?;
+    if reply[1] != 0x00 {
+        return Err(format!("SOCKS proxy could not connect (code {})", reply[1]));
+    }
+    // Skip the address the proxy bound, which is of no use here.
+    let bound = match reply[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => usize::from(stream.read_u8().await.map_err(io)?),
+        _ => return Err("SOCKS error: invalid reply".to_string()),
+    };
+    let mut rest = vec![0; bound + 2];
+    stream.read_exact(&mut rest).await.map_err(io)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::future::Future;
+
+    use tokio::net::TcpListener;
+
+    /// Run `proxy` on the first connection to a local listener, and return
+    /// the listener's port.
+    async fn fake_proxy<F>(proxy: impl FnOnce(TcpStream) -> F + Send + 'static) -> u16
+    where
+        F: Future<Output = ()> + Send,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            proxy(stream).await;
+        });
+        port
+    }
+
+    async fn read_head(stream: &mut TcpStream) -> String {
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        String::from_utf8(head).unwrap()
+    }
+
+    async fn read_greeting(stream: &mut TcpStream) -> String {
+        let mut greeting = [0; 5];
+        stream.read_exact(&mut greeting).await.unwrap();
+        String::from_utf8(greeting.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn connect_tunnels_through_an_http_proxy() {
+        let port = fake_proxy(|mut stream| async move {
+            let head = read_head(&mut stream).await;
+            assert!(head.starts_with("CONNECT example.com:443 HTTP/1.1\r\n"));
+            assert!(head.contains("Proxy-Authorization: Basic dXNlcjpwYXNz\r\n"));
+            // The greeting arrives with the response, and must be left for the caller.
+            stream
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\nhello")
+                .await
+                .unwrap();
+        })
+        .await;
+        let route = Route::Http {
+            host: "127.0.0.1".to_string(),
+            port,
+            auth: Some(HeaderValue::from_static("Basic dXNlcjpwYXNz")),
+        };
+
+        let mut stream = route.connect("example.com", 443).await.unwrap();
+        assert_eq!(read_greeting(&mut stream).await, "hello");
+    }
+
+    #[tokio::test]
+    async fn a_refused_connect_is_an_error() {
+        let port = fake_proxy(|mut stream| async move {
+            read_head(&mut stream).await;
+            stream
+                .write_all(b"HTTP/1.1 403 Forbidden\r\n\r\n")
+                .await
+                .unwrap();
+        })
+        .await;
+        let route = Route::Http {
+            host: "127.0.0.1".to_string(),
+            port,
+            auth: None,
+        };
+
+        let error = route.connect("example.com", 80).await.unwrap_err();
+        assert_eq!(error, "Proxy refused the tunnel: HTTP/1.1 403 Forbidden");
+    }
+
+    #[tokio::test]
+    async fn socks5_sends_the_name_for_remote_dns() {
+        let port = fake_proxy(|mut stream| async move {
+            let mut greeting = [0; 3];
+            stream.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [0x05, 0x01, 0x00]);
+            stream.write_all(&[0x05, 0x00]).await.unwrap();
+
+            let mut request = [0; 5 + 11 + 2];
+            stream.read_exact(&mut request).await.unwrap();
+            assert_eq!(request[..5], [0x05, 0x01, 0x00, 0x03, 11]);
+            assert_eq!(&request[5..16], b"example.com");
+            assert_eq!(request[16..], 80_u16.to_be_bytes());
+            stream
+                .write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+                .await
+                .unwrap();
+            stream.write_all(b"hello").await.unwrap();
+        })
+        .await;
+        let route = Route::Socks5 {
+            host: "127.0.0.1".to_string(),
+            port,
+            auth: None,
+            remote_dns: true,
+        };
+
+        let mut stream = route.connect("example.com", 80).await.unwrap();
+        assert_eq!(read_greeting(&mut stream).await, "hello");
+    }
+
+    #[tokio::test]
+    async fn socks5_authenticates_with_a_password() {
+        let port = fake_proxy(|mut stream| async move {
+            let mut greeting = [0; 3];
+            stream.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [0x05, 0x01, 0x02]);
+            stream.write_all(&[0x05, 0x02]).await.unwrap();
+
+            let mut credentials = [0; 1 + 1 + 4 + 1 + 4];
+            stream.read_exact(&mut credentials).await.unwrap();
+            assert_eq!(credentials, *b"\x01\x04user\x04pass");
+            stream.write_all(&[0x01, 0x00]).await.unwrap();
+
+            let mut request = [0; 4 + 4 + 2];
+            stream.read_exact(&mut request).await.unwrap();
+            assert_eq!(request[..8], [0x05, 0x01, 0x00, 0x01, 127, 0, 0, 1]);
+            stream
+                .write_all(&[0x05, 0x00, 0x00, 0x03, 3, b'f', b'o', b'o', 0, 80])
+                .await
+                .unwrap();
+            stream.write_all(b"hello").await.unwrap();
+        })
+        .await;
+        let route = Route::Socks5 {
+            host: "127.0.0.1".to_string(),
+            port,
+            auth: Some(("user".to_string(), "pass".to_string())),
+            remote_dns: false,
+        };
+
+        let mut stream = route.connect("127.0.0.1", 8080).await.unwrap();
+        assert_eq!(read_greeting(&mut stream).await, "hello");
+    }
+
+    #[tokio::test]
+    async fn socks5_reports_rejected_credentials() {
+        let port = fake_proxy(|mut stream| async move {
+            let mut greeting = [0; 3];
+            stream.read_exact(&mut greeting).await.unwrap();
+            stream.write_all(&[0x05, 0x02]).await.unwrap();
+            let mut credentials = [0; 11];
+            stream.read_exact(&mut credentials).await.unwrap();
+            stream.write_all(&[0x01, 0x01]).await.unwrap();
+        })
+        .await;
+        let route = Route::Socks5 {
+            host: "127.0.0.1".to_string(),
+            port,
+            auth: Some(("user".to_string(), "pass".to_string())),
+            remote_dns: true,
+        };
+
+        let error = route.connect("example.com", 80).await.unwrap_err();
+        assert_eq!(error, "SOCKS proxy rejected the credentials");
+    }
+}
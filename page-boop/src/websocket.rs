@@ -0,0 +1,176 @@
+//! WebSocket client run as an iced [`Subscription`](iced::Subscription),
+//! plus a tiny echo server to talk to when offline.
+
+use std::sync::Arc;
+
+use iced::futures::{FutureExt, SinkExt, Stream, StreamExt, TryStreamExt, channel::mpsc};
+use iced::stream;
+use tokio::net::TcpListener;
+use tokio_tungstenite::{Connector, tungstenite};
+
+use crate::network::NetworkSettings;
+
+/// Where the bundled echo server listens by default.
+pub const ECHO_ADDR: &str = "127.0.0.1:9001";
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Connected(Connection),
+    Received(String),
+    /// The connection is gone, with the reason why.
+    Disconnected(String),
+}
+
+/// Sending half of an open connection.
+#[derive(Debug, Clone)]
+pub struct Connection(mpsc::Sender<String>);
+
+impl Connection {
+    pub async fn send(mut self, text: String) -> Result<(), String> {
+        self.0
+            .send(text)
+            .await
+            .map_err(|_| "Not connected".to_string())
+    }
+}
+
+/// The `ws`/`wss` URL matching an `http`/`https` one.
+pub fn ws_url(mut url: url::Url) -> url::Url {
+    let scheme = match url.scheme() {
+        "http" => "ws",
+        "https" => "wss",
+        _ => return url,
+    };
+    // Both are special schemes, so switching between them cannot fail.
+    let _ = url.set_scheme(scheme);
+    url
+}
+
+/// Connect to `url` with the given proxy and TLS settings and report
+/// everything that happens on the connection.
+///
+/// The stream ends after [`Event::Disconnected`].
+pub fn connect((url, network): &(url::Url, NetworkSettings)) -> impl Stream<Item = Event> + use<> {
+    let url = url.clone();
+    let network = network.clone();
+    stream::channel(100, async move |mut output| {
+        let reason = match run(&url, &network, &mut output).await {
+            Ok(()) => "Connection closed".to_string(),
+            Err(error) => error,
+        };
+        let _ = output.send(Event::Disconnected(reason)).await;
+    })
+}
+
+async fn run(
+    url: &url::Url,
+    network: &NetworkSettings,
+    output: &mut mpsc::Sender<Event>,
+) -> Result<(), String> {
+    let host = match url.host() {
+        Some(url::Host::Ipv6(address)) => address.to_string(),
+        Some(host) => host.to_string(),
+        None => return Err("URL has no host".to_string()),
+    };
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| "URL has no port".to_string())?;
+    let stream = network.route(url)?.connect(&host, port).await?;
+    let connector = if url.scheme() == "wss" {
+        Connector::Rustls(Arc::new(network.tls_config().await?))
+    } else {
+        Connector::Plain
+    };
+    let (socket, _) = tokio_tungstenite::client_async_tls_with_config(
+        url.as_str(),
+        stream,
+        None,
+        Some(connector),
+    )
+    .await
+    .map_err(|e| format!("Connect error: {e}"))?;
+    let (mut write, mut read) = socket.split();
+
+    let (sender, mut outgoing) = mpsc::channel(100);
+    let _ = output.send(Event::Connected(Connection(sender))).await;
+
+    loop {
+        iced::futures::select! {
+            received = read.next().fuse() => match received {
+                Some(Ok(tungstenite::Message::Text(text))) => {
+                    let _ = output.send(Event::Received(text.to_string())).await;
+                }
+                Some(Ok(tungstenite::Message::Binary(bytes))) => {
+                    let _ = output
+                        .send(Event::Received(format!("<{} binary bytes>", bytes.len())))
+                        .await;
+                }
+                Some(Ok(tungstenite::Message::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(format!("Error: {e}")),
+            },
+            text = outgoing.next() => match text {
+                Some(text) => write
+                    .send(tungstenite::Message::text(text))
+                    .await
+                    .map_err(|e| format!("Error sending: {e}"))?,
+                // Every `Connection` was dropped; nobody can send anymore.
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+/// Accept WebSocket clients on `addr` and send every message back.
+///
+/// Only returns if the listener fails.
+pub async fn serve_echo(addr: String) -> Result<(), String> {
+    let listener = TcpListener::bind(&addr)
+        .await
+        .map_err(|e| format!("Error listening on {addr}: {e}"))?;
+    echo(listener).await
+}
+
+async fn echo(listener: TcpListener) -> Result<(), String> {
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| format!("Error accepting connection: {e}"))?;
+        tokio::spawn(async move {
+            let Ok(socket) = tokio_tungstenite::accept_async(stream).await else {
+                return;
+            };
+            let (write, read) = socket.split();
+            // Control frames are answered by tungstenite itself.
+            let _ = read
+                .try_filter(|message| std::future::ready(message.is_text() || message.is_binary()))
+                .forward(write)
+                .await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn echo_server_sends_messages_back() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(echo(listener));
+
+        let url = url::Url::parse(&format!("ws://{addr}")).unwrap();
+        let mut events = Box::pin(connect(&(url, NetworkSettings::default())));
+
+        let Some(Event::Connected(connection)) = events.next().await else {
+            panic!("expected a connection");
+        };
+        connection.clone().send("boop".to_string()).await.unwrap();
+        assert!(matches!(events.next().await, Some(Event::Received(text)) if text == "boop"));
+
+        drop(connection);
+        assert!(matches!(events.next().await, Some(Event::Disconnected(_))));
+    }
+}
@@ -1,36 +1,157 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::f32::consts::{FRAC_PI_4, TAU};
+use std::ops::Range;
+use std::time::Duration;
+
 use iced::{
-    Color, Rectangle,
-    widget::canvas::{self, Frame, Path, Stroke},
+    Color, Font, Point, Rectangle, Size, Theme, Vector, mouse,
+    time::Instant,
+    widget::{
+        canvas::{self, Event, Frame, Path, Stroke, path},
+        svg, text,
+    },
 };
-use rand::Rng;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// Physics always advances in steps of this size, however ticks arrive.
+const FIXED_STEP: Duration = Duration::from_micros(16_667);
+
+/// Longest gap simulated after a stall; beyond it the simulation just slows
+/// down instead of running hundreds of catch-up steps.
+const MAX_FRAME: Duration = Duration::from_millis(250);
+
+/// Share of the approaching speed kept after two circles collide.
+pub const DEFAULT_RESTITUTION: f32 = 0.9;
+
+/// A press that moves less than this many pixels is a click, which pops.
+const CLICK_SLOP: f32 = 4.0;
+
+/// Holding still this long before releasing drops the circle instead of flinging it.
+const FLING_WINDOW: Duration = Duration::from_millis(100);
+
+/// Fastest fling, in pixels per second.
+const MAX_FLING: f32 = 2000.0;
+
+const PARTICLES_PER_POP: usize = 16;
+/// Seconds a popped circle's particles live; every particle fades out over
+/// its last this many seconds.
+const PARTICLE_LIFETIME: f32 = 0.6;
+/// Pixels per second squared.
+const PARTICLE_GRAVITY: f32 = 600.0;
+
+const CONFETTI_PIECES: usize = 150;
+/// Seconds a piece of confetti lives.
+const CONFETTI_LIFETIME: f32 = 3.0;
+
+const SNOWFLAKES_PER_SECOND: f32 = 20.0;
+
+/// What plays over a slide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Effect {
+    #[default]
+    None,
+    /// Circles to grab, fling and pop, spawned by a subscription.
+    Chaos,
+    /// Nothing until [`Message::Confetti`] fires a burst.
+    Confetti,
+    Snow,
+}
+
+/// Circles spawned at once by the stress test.
+pub const STRESS_CIRCLES: usize = 2000;
+
+/// Positions remembered for a motion trail, one per physics step.
+const TRAIL_LENGTH: usize = 12;
+
+/// Weight of the newest sample in the smoothed HUD numbers.
+const SMOOTHING: f32 = 0.1;
 
+/// The cursor stops attracting circles farther away than this many pixels.
+const ATTRACTOR_RANGE: f32 = 400.0;
+
+/// Fields acting on every circle that isn't held.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Forces {
+    /// Downwards pull, in pixels per second squared.
+    pub gravity: f32,
+    /// Sideways push, in pixels per second squared; negative blows left.
+    pub wind: f32,
+    /// Share of its velocity a circle loses per second.
+    pub drag: f32,
+    /// Pull towards the cursor, in pixels per second squared; negative repels.
+    pub attraction: f32,
+}
+
+impl Forces {
+    /// Acceleration of a circle centered at `at` with the cursor at `pointer`.
+    fn acceleration(&self, at: Point, pointer: Option<Point>) -> Vector {
+        let mut acceleration = Vector::new(self.wind, self.gravity);
+        if let Some(pointer) = pointer {
+            let distance = at.distance(pointer);
+            if distance > 1.0 && distance < ATTRACTOR_RANGE {
+                // Strongest right at the cursor, fading out towards the edge of the range.
+                let strength = self.attraction * (1.0 - distance / ATTRACTOR_RANGE);
+                acceleration += (pointer - at) * (strength / distance);
+            }
+        }
+        acceleration
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Circle,
+    Square,
+    Triangle,
+    /// The Iced logo.
+    Logo,
+}
+
+impl Shape {
+    const ALL: [Shape; 4] = [Shape::Circle, Shape::Square, Shape::Triangle, Shape::Logo];
+}
+
+/// A bouncing object. Whatever its [`Shape`], it collides as the circle
+/// around it.
 #[derive(Clone)]
 pub struct ChaosCircle {
     pub x: f32,
     pub y: f32,
+    /// Pixels per second.
     pub vx: f32,
+    /// Pixels per second.
     pub vy: f32,
     pub radius: f32,
-    pub color: Color,
+    pub shape: Shape,
+    /// Radians.
+    pub angle: f32,
+    /// Radians per second.
+    pub spin: f32,
+    /// Index into the theme's [`palette`].
+    pub tint: usize,
+    pub alpha: f32,
+    /// Previous centers, oldest first.
+    pub trail: VecDeque<Point>,
 }
 
 impl ChaosCircle {
-    pub fn random(bounds_width: f32, bounds_height: f32) -> Self {
-        let mut rng = rand::rng();
-
-        let radius = rng.random_range(20.0..80.0);
-        let x = rng.random_range(radius..bounds_width - radius);
-        let y = rng.random_range(radius..bounds_height - radius);
+    pub fn random(rng: &mut impl Rng, bounds_width: f32, bounds_height: f32) -> Self {
+        Self::random_sized(rng, 20.0..80.0, bounds_width, bounds_height)
+    }
 
-        let vx = rng.random_range(-2.0..2.0);
-        let vy = rng.random_range(-2.0..2.0);
+    fn random_sized(
+        rng: &mut impl Rng,
+        radii: Range<f32>,
+        bounds_width: f32,
+        bounds_height: f32,
+    ) -> Self {
+        let radius = rng.random_range(radii);
+        let x = random_position(rng, radius, bounds_width);
+        let y = random_position(rng, radius, bounds_height);
 
-        let color = Color {
-            r: rng.random_range(0.0..1.0),
-            g: rng.random_range(0.0..1.0),
-            b: rng.random_range(0.0..1.0),
-            a: rng.random_range(0.3..0.9),
-        };
+        let vx = rng.random_range(-120.0..120.0);
+        let vy = rng.random_range(-120.0..120.0);
 
         Self {
             x,
@@ -38,70 +159,724 @@ impl ChaosCircle {
             vx,
             vy,
             radius,
-            color,
+            shape: Shape::ALL[rng.random_range(0..Shape::ALL.len())],
+            angle: rng.random_range(0.0..TAU),
+            spin: rng.random_range(-2.0..2.0),
+            tint: rng.random_range(0..PALETTE_SIZE),
+            alpha: ALPHAS[rng.random_range(0..ALPHAS.len())],
+            trail: VecDeque::new(),
         }
     }
 
-    fn update_physics(&mut self, bounds_width: f32, bounds_height: f32) {
-        self.x += self.vx;
-        self.y += self.vy;
+    /// Proportional to the area, so big circles shove small ones around.
+    fn mass(&self) -> f32 {
+        self.radius * self.radius
+    }
+
+    fn update_physics(
+        &mut self,
+        dt: f32,
+        bounds_width: f32,
+        bounds_height: f32,
+        forces: &Forces,
+        pointer: Option<Point>,
+    ) {
+        let acceleration = forces.acceleration(Point::new(self.x, self.y), pointer);
+        let damping = (1.0 - forces.drag * dt).max(0.0);
+        self.vx = (self.vx + acceleration.x * dt) * damping;
+        self.vy = (self.vy + acceleration.y * dt) * damping;
+
+        self.x += self.vx * dt;
+        self.y += self.vy * dt;
+        self.angle = (self.angle + self.spin * dt) % TAU;
 
         if self.x - self.radius < 0.0 || self.x + self.radius > bounds_width {
             self.vx = -self.vx;
-            self.x = self.x.clamp(self.radius, bounds_width - self.radius);
+            self.x = keep_inside(self.x, self.radius, bounds_width);
         }
         if self.y - self.radius < 0.0 || self.y + self.radius > bounds_height {
             self.vy = -self.vy;
-            self.y = self.y.clamp(self.radius, bounds_height - self.radius);
+            self.y = keep_inside(self.y, self.radius, bounds_height);
+        }
+    }
+}
+
+/// A center coordinate keeping a circle inside `extent`, or the middle if
+/// the circle is too big to fit.
+fn random_position(rng: &mut impl Rng, radius: f32, extent: f32) -> f32 {
+    if extent > 2.0 * radius {
+        rng.random_range(radius..extent - radius)
+    } else {
+        extent / 2.0
+    }
+}
+
+/// Clamp a center coordinate so the circle stays inside `extent`, centering
+/// it if it is too big to fit.
+fn keep_inside(position: f32, radius: f32, extent: f32) -> f32 {
+    if extent >= 2.0 * radius {
+        position.clamp(radius, extent - radius)
+    } else {
+        extent / 2.0
+    }
+}
+
+/// Bounce every overlapping pair of circles off each other.
+///
+/// Sweep and prune: with circles sorted by their left edge, only those whose
+/// x extents overlap are tested, instead of every pair.
+fn resolve_collisions(circles: &mut [ChaosCircle], restitution: f32) {
+    let mut order: Vec<usize> = (0..circles.len()).collect();
+    order.sort_by(|&a, &b| {
+        let left = |i: usize| circles[i].x - circles[i].radius;
+        left(a).total_cmp(&left(b))
+    });
+
+    let mut active: Vec<usize> = Vec::new();
+    for i in order {
+        let left = circles[i].x - circles[i].radius;
+        active.retain(|&j| circles[j].x + circles[j].radius >= left);
+        for &j in &active {
+            let (a, b) = pair_mut(circles, j, i);
+            collide(a, b, restitution);
         }
+        active.push(i);
+    }
+}
+
+fn pair_mut<T>(items: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+    if a < b {
+        let (low, high) = items.split_at_mut(b);
+        (&mut low[a], &mut high[0])
+    } else {
+        let (low, high) = items.split_at_mut(a);
+        (&mut high[0], &mut low[b])
+    }
+}
+
+/// Separate two overlapping circles and exchange momentum along the line
+/// between their centers.
+fn collide(a: &mut ChaosCircle, b: &mut ChaosCircle, restitution: f32) {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let reach = a.radius + b.radius;
+    let distance_sq = dx * dx + dy * dy;
+    if distance_sq >= reach * reach || distance_sq == 0.0 {
+        return;
+    }
+    let distance = distance_sq.sqrt();
+    let (nx, ny) = (dx / distance, dy / distance);
+
+    let (inv_a, inv_b) = (1.0 / a.mass(), 1.0 / b.mass());
+    let inv_total = inv_a + inv_b;
+
+    // Push apart so they don't stay stuck inside each other.
+    let overlap = reach - distance;
+    a.x -= nx * overlap * inv_a / inv_total;
+    a.y -= ny * overlap * inv_a / inv_total;
+    b.x += nx * overlap * inv_b / inv_total;
+    b.y += ny * overlap * inv_b / inv_total;
+
+    let approach = (b.vx - a.vx) * nx + (b.vy - a.vy) * ny;
+    if approach > 0.0 {
+        // Already moving apart.
+        return;
+    }
+    let impulse = -(1.0 + restitution) * approach / inv_total;
+    a.vx -= impulse * inv_a * nx;
+    a.vy -= impulse * inv_a * ny;
+    b.vx += impulse * inv_b * nx;
+    b.vy += impulse * inv_b * ny;
+}
+
+/// A spark, a piece of confetti or a snowflake.
+#[derive(Clone)]
+pub struct Particle {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    /// Pixels per second squared.
+    pub gravity: f32,
+    /// Radius while fully alive.
+    pub size: f32,
+    /// Seconds left to live.
+    pub life: f32,
+    /// Index into the theme's [`palette`]; `None` uses the theme's text color.
+    pub tint: Option<usize>,
+}
+
+impl Particle {
+    fn burst(rng: &mut impl Rng, circle: &ChaosCircle) -> impl Iterator<Item = Particle> {
+        let (x, y, radius, tint) = (circle.x, circle.y, circle.radius, circle.tint);
+        (0..PARTICLES_PER_POP).map(move |i| {
+            let angle = i as f32 / PARTICLES_PER_POP as f32 * std::f32::consts::TAU
+                + rng.random_range(-0.2..0.2);
+            let speed = rng.random_range(150.0..350.0);
+            Particle {
+                x: x + angle.cos() * radius * 0.5,
+                y: y + angle.sin() * radius * 0.5,
+                vx: angle.cos() * speed,
+                vy: angle.sin() * speed,
+                gravity: PARTICLE_GRAVITY,
+                size: 5.0,
+                life: PARTICLE_LIFETIME,
+                tint: Some(tint),
+            }
+        })
+    }
+
+    /// Confetti shot upwards from the bottom corners.
+    fn confetti(
+        rng: &mut impl Rng,
+        bounds_width: f32,
+        bounds_height: f32,
+    ) -> impl Iterator<Item = Particle> {
+        (0..CONFETTI_PIECES).map(move |i| {
+            let (x, direction) = if i % 2 == 0 {
+                (0.0, 1.0)
+            } else {
+                (bounds_width, -1.0)
+            };
+            let angle = rng.random_range(0.9..1.4_f32);
+            let speed = rng.random_range(600.0..1100.0);
+            Particle {
+                x,
+                y: bounds_height,
+                vx: angle.cos() * speed * direction,
+                vy: -angle.sin() * speed,
+                gravity: PARTICLE_GRAVITY,
+                size: rng.random_range(3.0..7.0),
+                life: rng.random_range(CONFETTI_LIFETIME / 2.0..CONFETTI_LIFETIME),
+                tint: Some(rng.random_range(0..PALETTE_SIZE)),
+            }
+        })
+    }
+
+    /// A snowflake somewhere above the top edge, living until it has fallen
+    /// past the bottom.
+    fn snowflake(rng: &mut impl Rng, bounds_width: f32, bounds_height: f32) -> Particle {
+        let vy = rng.random_range(40.0..100.0);
+        Particle {
+            x: rng.random_range(0.0..bounds_width.max(1.0)),
+            y: -10.0,
+            vx: rng.random_range(-20.0..20.0),
+            vy,
+            gravity: 0.0,
+            size: rng.random_range(2.0..5.0),
+            life: (bounds_height + 20.0) / vy + PARTICLE_LIFETIME,
+            tint: None,
+        }
+    }
+
+    fn update_physics(&mut self, dt: f32) {
+        self.x += self.vx * dt;
+        self.y += self.vy * dt;
+        self.vy += self.gravity * dt;
+        self.life -= dt;
     }
 }
 
 pub struct ChaosOverlay<'a> {
     pub circles: &'a [ChaosCircle],
+    pub particles: &'a [Particle],
+    /// The drawn circles. Every physics step with circles clears it, so it
+    /// only saves work on frames between steps, e.g. on displays refreshing
+    /// faster than the fixed step.
+    pub cache: &'a canvas::Cache,
+    pub logo: &'a svg::Handle,
+    /// Performance numbers to show in a corner, if any.
+    pub hud: Option<&'a Stats>,
+    /// Report cursor movement so the cursor can attract circles.
+    pub track_pointer: bool,
+}
+
+impl ChaosOverlay<'_> {
+    /// The topmost circle under `position`.
+    fn circle_at(&self, position: Point) -> Option<usize> {
+        self.circles
+            .iter()
+            .rposition(|circle| Point::new(circle.x, circle.y).distance(position) <= circle.radius)
+    }
+}
+
+const PALETTE_SIZE: usize = 6;
+
+/// Opacities objects are drawn with; only a few, so every object of one
+/// color and opacity is filled as a single path.
+const ALPHAS: [f32; 4] = [0.5, 0.6, 0.7, 0.8];
+
+/// Colors objects are drawn in, taken from the theme so they suit it.
+fn palette(theme: &Theme) -> [Color; PALETTE_SIZE] {
+    let palette = theme.extended_palette();
+    [
+        palette.primary.base.color,
+        palette.primary.strong.color,
+        palette.secondary.strong.color,
+        palette.success.base.color,
+        palette.warning.base.color,
+        palette.danger.base.color,
+    ]
+}
+
+/// Add an object's outline to a path.
+fn trace(builder: &mut path::Builder, object: &ChaosCircle) {
+    let center = Point::new(object.x, object.y);
+    let sides = match object.shape {
+        Shape::Circle | Shape::Logo => {
+            builder.circle(center, object.radius);
+            return;
+        }
+        Shape::Square => 4,
+        Shape::Triangle => 3,
+    };
+    // Corners touch the bounding circle.
+    for i in 0..sides {
+        let angle = object.angle + FRAC_PI_4 + i as f32 * TAU / sides as f32;
+        let corner = center + Vector::new(angle.cos(), angle.sin()) * object.radius;
+        if i == 0 {
+            builder.move_to(corner);
+        } else {
+            builder.line_to(corner);
+        }
+    }
+    builder.close();
+}
+
+/// Opacity of the newest trail segment; older ones fade out from it.
+const TRAIL_ALPHA: f32 = 0.35;
+
+/// Fill every trail segment as a quad, batched into one path per color and
+/// age rather than stroking each segment on its own.
+fn draw_trails(frame: &mut Frame, objects: &[ChaosCircle], colors: &[Color; PALETTE_SIZE]) {
+    for (tint, &color) in colors.iter().enumerate() {
+        let tinted: Vec<&ChaosCircle> = objects
+            .iter()
+            .filter(|object| object.tint == tint && !object.trail.is_empty())
+            .collect();
+        if tinted.is_empty() {
+            continue;
+        }
+        // Age 0 is the segment ending at the current center.
+        for age in 0..TRAIL_LENGTH {
+            let fade = (TRAIL_LENGTH - age) as f32 / (TRAIL_LENGTH + 1) as f32;
+            let segments = Path::new(|builder| {
+                for object in &tinted {
+                    let Some(end) = object.trail.len().checked_sub(age) else {
+                        continue;
+                    };
+                    let Some(start) = end.checked_sub(1) else {
+                        continue;
+                    };
+                    let to = object
+                        .trail
+                        .get(end)
+                        .copied()
+                        .unwrap_or(Point::new(object.x, object.y));
+                    quad(builder, object.trail[start], to, object.radius * fade);
+                }
+            });
+            frame.fill(
+                &segments,
+                Color {
+                    a: TRAIL_ALPHA * fade,
+                    ..color
+                },
+            );
+        }
+    }
 }
 
-impl<Message> canvas::Program<Message> for ChaosOverlay<'_> {
-    type State = ();
+/// Add the rectangle a line of `width` from `from` to `to` would cover.
+fn quad(builder: &mut path::Builder, from: Point, to: Point, width: f32) {
+    let length = from.distance(to);
+    if length == 0.0 {
+        return;
+    }
+    let normal = Vector::new(from.y - to.y, to.x - from.x) * (width / 2.0 / length);
+    builder.move_to(from + normal);
+    builder.line_to(to + normal);
+    builder.line_to(to - normal);
+    builder.line_to(from - normal);
+    builder.close();
+}
+
+/// Draw the trails, then fill the shapes as one path per color and opacity
+/// and stroke all their outlines as another. Logos are sprites, drawn last.
+fn draw_objects(
+    frame: &mut Frame,
+    objects: &[ChaosCircle],
+    logo: &svg::Handle,
+    colors: &[Color; PALETTE_SIZE],
+    outline: Color,
+) {
+    draw_trails(frame, objects, colors);
+
+    let shapes = || objects.iter().filter(|object| object.shape != Shape::Logo);
+    for (tint, &color) in colors.iter().enumerate() {
+        for alpha in ALPHAS {
+            let mut batch = shapes()
+                .filter(|object| object.tint == tint && object.alpha == alpha)
+                .peekable();
+            if batch.peek().is_none() {
+                continue;
+            }
+            let fills = Path::new(|builder| batch.for_each(|object| trace(builder, object)));
+            frame.fill(&fills, Color { a: alpha, ..color });
+        }
+    }
+
+    let outlines = Path::new(|builder| shapes().for_each(|object| trace(builder, object)));
+    frame.stroke(
+        &outlines,
+        Stroke::default().with_color(outline).with_width(2.0),
+    );
+
+    for object in objects.iter().filter(|object| object.shape == Shape::Logo) {
+        let size = Size::new(object.radius * 2.0, object.radius * 2.0);
+        let corner = Point::new(object.x - object.radius, object.y - object.radius);
+        frame.draw_svg(Rectangle::new(corner, size), logo);
+    }
+}
+
+const HUD_LABELS: [&str; 6] = ["FPS", "frame", "step", "draw", "circles", "particles"];
+const HUD_LINE_HEIGHT: f32 = 20.0;
+const HUD_PADDING: f32 = 10.0;
+
+/// The HUD's panel in the top right corner of a canvas of `bounds`.
+fn hud_panel(bounds: Size) -> Rectangle {
+    let size = Size::new(
+        190.0,
+        HUD_LINE_HEIGHT * HUD_LABELS.len() as f32 + 2.0 * HUD_PADDING,
+    );
+    Rectangle::new(
+        Point::new(bounds.width - size.width - HUD_PADDING, HUD_PADDING),
+        size,
+    )
+}
+
+fn hud_text(content: String, position: Point) -> canvas::Text {
+    canvas::Text {
+        content,
+        position,
+        color: Color::WHITE,
+        size: 16.into(),
+        font: Font::MONOSPACE,
+        ..canvas::Text::default()
+    }
+}
+
+/// The parts of the HUD that stay the same: the panel and the labels.
+fn draw_hud_panel(frame: &mut Frame) {
+    let panel = hud_panel(frame.size());
+    frame.fill_rectangle(
+        panel.position(),
+        panel.size(),
+        Color::from_rgba(0.0, 0.0, 0.0, 0.7),
+    );
+    for (i, label) in HUD_LABELS.iter().enumerate() {
+        let offset = Vector::new(HUD_PADDING, HUD_PADDING + HUD_LINE_HEIGHT * i as f32);
+        frame.fill_text(hud_text(label.to_string(), panel.position() + offset));
+    }
+}
+
+/// The numbers, right-aligned next to the labels of [`draw_hud_panel`].
+fn draw_hud_values(frame: &mut Frame, values: [String; HUD_LABELS.len()]) {
+    let panel = hud_panel(frame.size());
+    for (i, value) in values.into_iter().enumerate() {
+        let position = Point::new(
+            panel.x + panel.width - HUD_PADDING,
+            panel.y + HUD_PADDING + HUD_LINE_HEIGHT * i as f32,
+        );
+        frame.fill_text(canvas::Text {
+            align_x: text::Alignment::Right,
+            ..hud_text(value, position)
+        });
+    }
+}
+
+/// Pointer state of the overlay; where circles are lives in [`Chaos`].
+#[derive(Default)]
+pub struct Interaction {
+    hovered: Option<usize>,
+    drag: Option<Drag>,
+    /// How long the previous frame took to draw, shown in the HUD.
+    draw_time: Cell<Duration>,
+    /// The HUD's panel and labels, redrawn only when the canvas is resized.
+    hud: canvas::Cache,
+}
+
+struct Drag {
+    index: usize,
+    /// From the cursor to the circle's center.
+    offset: Vector,
+    position: Point,
+    at: Instant,
+    /// Smoothed cursor velocity, in pixels per second.
+    velocity: Vector,
+    /// Total distance moved since the press.
+    travel: f32,
+}
+
+impl canvas::Program<Message> for ChaosOverlay<'_> {
+    type State = Interaction;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: &Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let position = cursor.position_in(bounds)?;
+                let index = self.circle_at(position)?;
+                let circle = &self.circles[index];
+                state.drag = Some(Drag {
+                    index,
+                    offset: Point::new(circle.x, circle.y) - position,
+                    position,
+                    at: Instant::now(),
+                    velocity: Vector::ZERO,
+                    travel: 0.0,
+                });
+                Some(canvas::Action::publish(Message::Grabbed(index)).and_capture())
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                let Some(drag) = &mut state.drag else {
+                    let pointer = cursor.position_in(bounds);
+                    if self.track_pointer {
+                        state.hovered = pointer.and_then(|position| self.circle_at(position));
+                        return Some(canvas::Action::publish(Message::PointerMoved(pointer)));
+                    }
+                    let hovered = pointer.and_then(|position| self.circle_at(position));
+                    if hovered == state.hovered {
+                        return None;
+                    }
+                    state.hovered = hovered;
+                    return Some(canvas::Action::request_redraw());
+                };
+
+                let position = cursor.position_from(bounds.position())?;
+                let now = Instant::now();
+                let dt = now.duration_since(drag.at).as_secs_f32();
+                if dt > 0.0 {
+                    let instant = (position - drag.position) * (1.0 / dt);
+                    drag.velocity = drag.velocity * 0.5 + instant * 0.5;
+                }
+                drag.travel += drag.position.distance(position);
+                drag.position = position;
+                drag.at = now;
+                Some(
+                    canvas::Action::publish(Message::Dragged(position + drag.offset)).and_capture(),
+                )
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                let drag = state.drag.take()?;
+                let message = if drag.travel < CLICK_SLOP {
+                    Message::Popped(drag.index)
+                } else if drag.at.elapsed() > FLING_WINDOW {
+                    Message::Released(Vector::ZERO)
+                } else {
+                    Message::Released(drag.velocity)
+                };
+                Some(canvas::Action::publish(message).and_capture())
+            }
+            Event::Mouse(mouse::Event::CursorLeft) if self.track_pointer => {
+                Some(canvas::Action::publish(Message::PointerMoved(None)))
+            }
+            _ => None,
+        }
+    }
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         renderer: &iced::Renderer,
-        _theme: &iced::Theme,
+        theme: &Theme,
         bounds: Rectangle,
-        _cursor: iced::mouse::Cursor,
+        cursor: mouse::Cursor,
     ) -> Vec<canvas::Geometry> {
-        let mut frame = Frame::new(renderer, bounds.size());
+        let started = Instant::now();
 
-        for circle in self.circles {
-            let center = iced::Point::new(circle.x, circle.y);
-            let path = Path::circle(center, circle.radius);
+        let colors = palette(theme);
+        let extended = theme.extended_palette();
+        let outline = extended.background.strong.color;
 
-            frame.fill(&path, circle.color);
+        // Only frames between physics steps reuse this layer.
+        let circles = self.cache.draw(renderer, bounds.size(), |frame| {
+            draw_objects(frame, self.circles, self.logo, &colors, outline);
+        });
 
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        let highlighted = match &state.drag {
+            Some(drag) => Some(drag.index),
+            None => cursor
+                .position_in(bounds)
+                .and_then(|position| self.circle_at(position)),
+        };
+        if let Some(object) = highlighted.and_then(|i| self.circles.get(i)) {
+            let path = Path::new(|builder| trace(builder, object));
+            if object.shape != Shape::Logo {
+                frame.fill(
+                    &path,
+                    Color {
+                        a: (object.alpha + 0.2).min(1.0),
+                        ..colors[object.tint]
+                    },
+                );
+            }
             frame.stroke(
                 &path,
-                Stroke::default().with_color(Color::BLACK).with_width(2.0),
+                Stroke::default()
+                    .with_color(extended.background.base.text)
+                    .with_width(4.0),
+            );
+        }
+
+        for particle in self.particles {
+            let alive = (particle.life / PARTICLE_LIFETIME).clamp(0.0, 1.0);
+            let color = particle
+                .tint
+                .map_or(extended.background.base.text, |tint| colors[tint]);
+            frame.fill(
+                &Path::circle(
+                    Point::new(particle.x, particle.y),
+                    particle.size * (0.2 + 0.8 * alive),
+                ),
+                Color { a: alive, ..color },
+            );
+        }
+
+        let mut layers = vec![circles, frame.into_geometry()];
+        if let Some(stats) = self.hud {
+            let ms = |duration: Duration| format!("{:.2} ms", duration.as_secs_f64() * 1000.0);
+            layers.push(state.hud.draw(renderer, bounds.size(), draw_hud_panel));
+            let mut values = Frame::new(renderer, bounds.size());
+            draw_hud_values(
+                &mut values,
+                [
+                    format!("{:.1}", stats.fps),
+                    ms(stats.frame_time),
+                    ms(stats.step_time),
+                    ms(state.draw_time.get()),
+                    self.circles.len().to_string(),
+                    self.particles.len().to_string(),
+                ],
             );
+            layers.push(values.into_geometry());
+        }
+
+        state.draw_time.set(started.elapsed());
+        layers
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.drag.is_some() {
+            mouse::Interaction::Grabbing
+        } else if cursor
+            .position_in(bounds)
+            .and_then(|position| self.circle_at(position))
+            .is_some()
+        {
+            mouse::Interaction::Grab
+        } else {
+            mouse::Interaction::default()
         }
+    }
+}
+
+/// Smoothed timings of the simulation, for the HUD.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stats {
+    pub fps: f32,
+    /// Time between ticks.
+    pub frame_time: Duration,
+    /// Time spent simulating during a tick.
+    pub step_time: Duration,
+}
 
-        vec![frame.into_geometry()]
+impl Stats {
+    fn record(&mut self, frame_time: Duration, step_time: Duration) {
+        let smooth = |old: Duration, new: Duration| {
+            if old.is_zero() {
+                new
+            } else {
+                old.mul_f32(1.0 - SMOOTHING) + new.mul_f32(SMOOTHING)
+            }
+        };
+        self.frame_time = smooth(self.frame_time, frame_time);
+        self.step_time = smooth(self.step_time, step_time);
+        self.fps = if self.frame_time.is_zero() {
+            0.0
+        } else {
+            1.0 / self.frame_time.as_secs_f32()
+        };
     }
 }
 
 pub struct Chaos {
+    effect: Effect,
     circles: Vec<ChaosCircle>,
+    particles: Vec<Particle>,
+    /// Snowflakes owed by the time simulated so far, less than one after a step.
+    snow_due: f32,
+    /// Circle being dragged; physics leaves it alone.
+    held: Option<usize>,
+    /// Every visit to the slide replays the same simulation from this seed.
+    seed: u64,
+    rng: StdRng,
     paused: bool,
     canvas_size: (f32, f32),
+    restitution: f32,
+    forces: Forces,
+    /// Cursor position over the canvas, which attracts circles.
+    pointer: Option<Point>,
+    /// The drawn circles; cleared by every step that moves them.
+    cache: canvas::Cache,
+    logo: svg::Handle,
+    trails: bool,
+    hud: bool,
+    stress: bool,
+    stats: Stats,
+    /// When the previous tick arrived; `None` after a pause.
+    last_tick: Option<Instant>,
+    /// Elapsed time not simulated yet, always less than one step after a tick.
+    accumulator: Duration,
+    focused: bool,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     SpawnChaos,
+    /// Fire a burst of confetti, whatever the effect.
+    Confetti,
     PanicChaos,
-    Tick,
+    Tick(Instant),
     WindowResized(f32, f32),
+    FocusChanged(bool),
+    RestitutionChanged(f32),
+    GravityChanged(f32),
+    WindChanged(f32),
+    DragChanged(f32),
+    AttractionChanged(f32),
+    ResetForces,
+    HudToggled(bool),
+    TrailsToggled(bool),
+    StressToggled(bool),
+    PointerMoved(Option<Point>),
+    Grabbed(usize),
+    /// Where the held circle's center should now be.
+    Dragged(Point),
+    /// The held circle was let go with this velocity, in pixels per second.
+    Released(Vector),
+    Popped(usize),
 }
 
 pub enum Action {
@@ -110,32 +885,159 @@ pub enum Action {
 
 impl Default for Chaos {
     fn default() -> Self {
+        Self::with_seed(rand::random())
+    }
+}
+
+impl Chaos {
+    pub fn with_seed(seed: u64) -> Self {
         Self {
+            effect: Effect::None,
             circles: Vec::new(),
+            particles: Vec::new(),
+            snow_due: 0.0,
+            held: None,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
             paused: false,
             canvas_size: (800.0, 600.0),
+            restitution: DEFAULT_RESTITUTION,
+            forces: Forces::default(),
+            pointer: None,
+            cache: canvas::Cache::new(),
+            logo: svg::Handle::from_memory(crate::ICED_LOGO),
+            trails: true,
+            hud: false,
+            stress: false,
+            stats: Stats::default(),
+            last_tick: None,
+            accumulator: Duration::ZERO,
+            focused: true,
         }
     }
-}
 
-impl Chaos {
     #[must_use]
     pub fn update(&mut self, message: Message) -> Action {
         match message {
             Message::SpawnChaos => {
-                if !self.paused {
+                if self.effect == Effect::Chaos && !self.paused && self.focused && !self.stress {
                     let (w, h) = self.canvas_size;
-                    self.circles.push(ChaosCircle::random(w, h));
+                    let circle = ChaosCircle::random(&mut self.rng, w, h);
+                    self.circles.push(circle);
+                    self.cache.clear();
                 }
             }
+            Message::Confetti => {
+                let (w, h) = self.canvas_size;
+                self.particles
+                    .extend(Particle::confetti(&mut self.rng, w, h));
+            }
             Message::PanicChaos => {
                 self.circles.clear();
+                self.particles.clear();
+                self.held = None;
                 self.paused = true;
+                self.stress = false;
+                self.cache.clear();
+            }
+            Message::Tick(now) => {
+                if !self.focused {
+                    return Action::None;
+                }
+                let elapsed = self
+                    .last_tick
+                    .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+                self.last_tick = Some(now);
+
+                let started = Instant::now();
+                self.accumulator += elapsed.min(MAX_FRAME);
+                while self.accumulator >= FIXED_STEP {
+                    self.step(FIXED_STEP.as_secs_f32());
+                    self.accumulator -= FIXED_STEP;
+                }
+                if !elapsed.is_zero() {
+                    self.stats.record(elapsed, started.elapsed());
+                }
+                // Ticks stop until the next effect starts; don't count the gap.
+                if !self.is_running() {
+                    self.last_tick = None;
+                }
+            }
+            Message::FocusChanged(focused) => {
+                self.focused = focused;
+                // Don't count the time spent unfocused.
+                self.last_tick = None;
             }
-            Message::Tick => {
+            Message::RestitutionChanged(restitution) => {
+                self.restitution = restitution;
+            }
+            Message::GravityChanged(gravity) => self.forces.gravity = gravity,
+            Message::WindChanged(wind) => self.forces.wind = wind,
+            Message::DragChanged(drag) => self.forces.drag = drag,
+            Message::AttractionChanged(attraction) => self.forces.attraction = attraction,
+            Message::ResetForces => self.forces = Forces::default(),
+            Message::PointerMoved(pointer) => self.pointer = pointer,
+            Message::HudToggled(hud) => self.hud = hud,
+            Message::TrailsToggled(trails) => {
+                self.trails = trails;
+                if !trails {
+                    for circle in &mut self.circles {
+                        circle.trail.clear();
+                    }
+                    self.cache.clear();
+                }
+            }
+            Message::StressToggled(stress) => {
+                self.stress = stress;
+                self.circles.clear();
+                self.held = None;
+                if stress {
+                    let (w, h) = self.canvas_size;
+                    for _ in 0..STRESS_CIRCLES {
+                        let mut circle = ChaosCircle::random_sized(&mut self.rng, 3.0..8.0, w, h);
+                        // Plain paths, to measure the renderer rather than SVG rasterizing.
+                        if circle.shape == Shape::Logo {
+                            circle.shape = Shape::Circle;
+                        }
+                        self.circles.push(circle);
+                    }
+                }
+                self.cache.clear();
+            }
+            Message::Grabbed(index) => {
+                if let Some(circle) = self.circles.get_mut(index) {
+                    circle.vx = 0.0;
+                    circle.vy = 0.0;
+                    self.held = Some(index);
+                }
+            }
+            Message::Dragged(center) => {
                 let (w, h) = self.canvas_size;
-                for circle in &mut self.circles {
-                    circle.update_physics(w, h);
+                if let Some(circle) = self.held.and_then(|i| self.circles.get_mut(i)) {
+                    circle.x = keep_inside(center.x, circle.radius, w);
+                    circle.y = keep_inside(center.y, circle.radius, h);
+                    self.cache.clear();
+                }
+            }
+            Message::Released(velocity) => {
+                if let Some(circle) = self.held.take().and_then(|i| self.circles.get_mut(i)) {
+                    let speed = (velocity.x * velocity.x + velocity.y * velocity.y).sqrt();
+                    let scale = if speed > MAX_FLING {
+                        MAX_FLING / speed
+                    } else {
+                        1.0
+                    };
+                    circle.vx = velocity.x * scale;
+                    circle.vy = velocity.y * scale;
+                }
+            }
+            Message::Popped(index) => {
+                self.held = None;
+                if index < self.circles.len() {
+                    let circle = self.circles.remove(index);
+                    self.particles
+                        .extend(Particle::burst(&mut self.rng, &circle));
+                    self.cache.clear();
                 }
             }
             Message::WindowResized(width, height) => {
@@ -145,12 +1047,218 @@ impl Chaos {
         Action::None
     }
 
-    pub fn clear_and_unpause(&mut self) {
+    fn step(&mut self, dt: f32) {
+        let (w, h) = self.canvas_size;
+        for (i, circle) in self.circles.iter_mut().enumerate() {
+            if self.trails {
+                if circle.trail.len() == TRAIL_LENGTH {
+                    circle.trail.pop_front();
+                }
+                circle.trail.push_back(Point::new(circle.x, circle.y));
+            }
+            if self.held == Some(i) {
+                circle.vx = 0.0;
+                circle.vy = 0.0;
+            } else {
+                circle.update_physics(dt, w, h, &self.forces, self.pointer);
+            }
+        }
+        resolve_collisions(&mut self.circles, self.restitution);
+        // Snow and confetti alone leave the circle layer as it is.
+        if !self.circles.is_empty() {
+            self.cache.clear();
+        }
+
+        if self.effect == Effect::Snow && !self.paused {
+            self.snow_due += SNOWFLAKES_PER_SECOND * dt;
+            while self.snow_due >= 1.0 {
+                self.particles
+                    .push(Particle::snowflake(&mut self.rng, w, h));
+                self.snow_due -= 1.0;
+            }
+        }
+
+        for particle in &mut self.particles {
+            particle.update_physics(dt);
+        }
+        self.particles.retain(|particle| particle.life > 0.0);
+    }
+
+    /// Whether anything is moving, so ticks are needed.
+    pub fn is_running(&self) -> bool {
+        let continuous = match self.effect {
+            Effect::Snow => !self.paused,
+            Effect::Chaos => !self.circles.is_empty(),
+            Effect::None | Effect::Confetti => false,
+        };
+        continuous || !self.particles.is_empty()
+    }
+
+    /// Whether circles should keep being spawned.
+    pub fn is_spawning(&self) -> bool {
+        self.effect == Effect::Chaos && !self.paused && !self.stress
+    }
+
+    pub fn effect(&self) -> Effect {
+        self.effect
+    }
+
+    /// Start over with another effect, e.g. on a new slide.
+    pub fn set_effect(&mut self, effect: Effect) {
+        self.clear_and_unpause();
+        self.effect = effect;
+    }
+
+    fn clear_and_unpause(&mut self) {
         self.circles.clear();
+        self.particles.clear();
+        self.held = None;
         self.paused = false;
+        self.stress = false;
+        self.cache.clear();
+        self.last_tick = None;
+        self.accumulator = Duration::ZERO;
+        self.snow_due = 0.0;
+        self.rng = StdRng::seed_from_u64(self.seed);
     }
 
     pub fn circles(&self) -> &[ChaosCircle] {
         &self.circles
     }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn restitution(&self) -> f32 {
+        self.restitution
+    }
+
+    pub fn forces(&self) -> &Forces {
+        &self.forces
+    }
+
+    pub fn cache(&self) -> &canvas::Cache {
+        &self.cache
+    }
+
+    /// Forget the drawn objects, e.g. because the theme they were drawn for changed.
+    pub fn redraw(&self) {
+        self.cache.clear();
+    }
+
+    pub fn logo(&self) -> &svg::Handle {
+        &self.logo
+    }
+
+    pub fn trails(&self) -> bool {
+        self.trails
+    }
+
+    /// The numbers to show in the HUD, if it is on.
+    pub fn hud(&self) -> Option<&Stats> {
+        self.hud.then_some(&self.stats)
+    }
+
+    pub fn stress(&self) -> bool {
+        self.stress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(x: f32, y: f32, vx: f32, vy: f32, radius: f32) -> ChaosCircle {
+        ChaosCircle {
+            x,
+            y,
+            vx,
+            vy,
+            radius,
+            shape: Shape::Circle,
+            angle: 0.0,
+            spin: 0.0,
+            tint: 0,
+            alpha: 1.0,
+            trail: VecDeque::new(),
+        }
+    }
+
+    fn chaos_with(circles: Vec<ChaosCircle>) -> Chaos {
+        let mut chaos = Chaos::with_seed(7);
+        chaos.set_effect(Effect::Chaos);
+        let _ = chaos.update(Message::WindowResized(800.0, 600.0));
+        chaos.circles = circles;
+        chaos
+    }
+
+    #[test]
+    fn ticks_advance_in_fixed_steps() {
+        let mut chaos = chaos_with(vec![circle(100.0, 100.0, 60.0, -30.0, 10.0)]);
+        let t0 = Instant::now();
+
+        // The first tick only starts the clock.
+        let _ = chaos.update(Message::Tick(t0));
+        assert_eq!((chaos.circles[0].x, chaos.circles[0].y), (100.0, 100.0));
+
+        // Less than a step is carried over rather than simulated.
+        let _ = chaos.update(Message::Tick(t0 + FIXED_STEP / 2));
+        assert_eq!((chaos.circles[0].x, chaos.circles[0].y), (100.0, 100.0));
+
+        let _ = chaos.update(Message::Tick(t0 + FIXED_STEP * 3));
+        let dt = FIXED_STEP.as_secs_f32();
+        let (mut x, mut y) = (100.0_f32, 100.0_f32);
+        for _ in 0..3 {
+            x += 60.0 * dt;
+            y += -30.0 * dt;
+        }
+        assert_eq!((chaos.circles[0].x, chaos.circles[0].y), (x, y));
+        assert_eq!(chaos.circles[0].trail.len(), 3);
+    }
+
+    #[test]
+    fn circles_bounce_off_the_walls() {
+        let mut chaos = chaos_with(vec![circle(795.0, 300.0, 600.0, 0.0, 10.0)]);
+        let t0 = Instant::now();
+        let _ = chaos.update(Message::Tick(t0));
+        let _ = chaos.update(Message::Tick(t0 + FIXED_STEP));
+
+        assert_eq!(chaos.circles[0].x, 790.0);
+        assert_eq!(chaos.circles[0].vx, -600.0);
+    }
+
+    #[test]
+    fn the_same_seed_spawns_the_same_circles() {
+        let spawn = || {
+            let mut chaos = chaos_with(Vec::new());
+            for _ in 0..5 {
+                let _ = chaos.update(Message::SpawnChaos);
+            }
+            chaos
+                .circles()
+                .iter()
+                .map(|circle| (circle.x, circle.y, circle.radius))
+                .collect::<Vec<_>>()
+        };
+        let circles = spawn();
+        assert_eq!(circles.len(), 5);
+        assert_eq!(circles, spawn());
+    }
+
+    #[test]
+    fn circles_too_big_to_fit_are_centered() {
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(random_position(&mut rng, 50.0, 60.0), 30.0);
+        assert_eq!(random_position(&mut rng, 50.0, 100.0), 50.0);
+        assert_eq!(keep_inside(0.0, 50.0, 60.0), 30.0);
+        assert_eq!(keep_inside(90.0, 50.0, 100.0), 50.0);
+        assert_eq!(keep_inside(0.0, 10.0, 100.0), 10.0);
+
+        let mut chaos = chaos_with(vec![circle(10.0, 10.0, 100.0, 100.0, 400.0)]);
+        let t0 = Instant::now();
+        let _ = chaos.update(Message::Tick(t0));
+        let _ = chaos.update(Message::Tick(t0 + FIXED_STEP * 4));
+        assert_eq!((chaos.circles[0].x, chaos.circles[0].y), (400.0, 300.0));
+    }
 }
@@ -95,6 +95,7 @@ impl Default for App {
             page_boop: page_boop::PageBoop::with_style(page_boop::StyleConfig {
                 mono_font: FIRA_MONO,
                 subtitle_color: SUBTITLE_COLOR,
+                error_color: INCORRECT_COLOR,
                 text_size: TEXT_SIZE,
             }),
             terminal: terminal::Terminal::new(FIRA_MONO),
@@ -137,8 +138,31 @@ pub enum Message {
     Noop,
 }
 
+/// Seed for the chaos overlay from `--seed N` or `CHAOS_SEED`, so runs can
+/// be replayed exactly; random otherwise.
+fn chaos_seed() -> Option<u64> {
+    let args: Vec<String> = std::env::args().collect();
+    let arg = args
+        .iter()
+        .position(|arg| arg == "--seed")
+        .and_then(|i| args.get(i + 1).cloned());
+    arg.or_else(|| std::env::var("CHAOS_SEED").ok())
+        .and_then(|seed| seed.trim().parse().ok())
+}
+
 fn main() -> iced::Result {
-    iced::application(App::default, App::update, App::view)
+    let seed = chaos_seed();
+    let boot = move || {
+        let app = App {
+            chaos: seed.map_or_else(chaos::Chaos::default, chaos::Chaos::with_seed),
+            ..App::default()
+        };
+        let system_theme = iced::system::theme()
+            .map(|mode| Message::Theming(theming::Message::SystemModeChanged(mode)));
+        (app, system_theme)
+    };
+
+    iced::application(boot, App::update, App::view)
         .title("Iced Tutorial")
         .theme(App::theme)
         .subscription(App::subscription)
@@ -188,21 +212,31 @@ impl App {
             Event::Window(iced::window::Event::Resized(size)) => Some(Message::Chaos(
                 chaos::Message::WindowResized(size.width, size.height),
             )),
+            Event::Window(iced::window::Event::Focused) => {
+                Some(Message::Chaos(chaos::Message::FocusChanged(true)))
+            }
+            Event::Window(iced::window::Event::Unfocused) => {
+                Some(Message::Chaos(chaos::Message::FocusChanged(false)))
+            }
             _ => None,
         });
 
-        let screen = self.navigation.screen();
         let term_sub = self.terminal.subscription().map(Message::Terminal);
+        let boop_sub = self.page_boop.subscription().map(Message::PageBoop);
+
+        let system_theme = iced::system::theme_changes()
+            .map(|mode| Message::Theming(theming::Message::SystemModeChanged(mode)));
 
-        let mut subs = vec![events, term_sub];
+        let mut subs = vec![events, term_sub, boop_sub, system_theme];
 
-        if screen == Slide::Subscriptions || self.navigation.is_animating() {
+        if self.chaos.is_running() || self.navigation.is_animating() {
             subs.push(
-                iced::time::every(TICK_INTERVAL).map(|_| Message::Chaos(chaos::Message::Tick)),
+                iced::time::every(TICK_INTERVAL)
+                    .map(|now| Message::Chaos(chaos::Message::Tick(now))),
             );
         }
 
-        if screen == Slide::Subscriptions {
+        if self.chaos.is_spawning() {
             subs.push(
                 iced::time::every(CHAOS_SPAWN_INTERVAL)
                     .map(|_| Message::Chaos(chaos::Message::SpawnChaos)),
@@ -220,7 +254,7 @@ impl App {
                 match self.navigation.update(msg) {
                     navigation::Action::None => {}
                     navigation::Action::SlideChanged => {
-                        self.chaos.clear_and_unpause();
+                        self.chaos.set_effect(self.navigation.screen().effect());
                     }
                 }
                 Task::none()
@@ -233,13 +267,14 @@ impl App {
                 Task::none()
             }
 
-            Message::Theming(msg) => {
-                match self.theming.update(msg) {
-                    theming::Action::None => {}
-                    theming::Action::ThemeChanged => {}
+            Message::Theming(msg) => match self.theming.update(msg) {
+                theming::Action::None => Task::none(),
+                theming::Action::ThemeChanged => {
+                    self.chaos.redraw();
+                    Task::none()
                 }
-                Task::none()
-            }
+                theming::Action::Run(task) => task.map(Message::Theming),
+            },
 
             Message::Chaos(msg) => {
                 match self.chaos.update(msg) {
@@ -248,12 +283,10 @@ impl App {
                 Task::none()
             }
 
-            Message::Quiz(msg) => {
-                match self.quiz.update(msg) {
-                    quiz::Action::None => {}
-                }
-                Task::none()
-            }
+            Message::Quiz(msg) => match self.quiz.update(msg) {
+                quiz::Action::None => Task::none(),
+                quiz::Action::Correct => Task::done(Message::Chaos(chaos::Message::Confetti)),
+            },
 
             Message::PageBoop(msg) => match self.page_boop.update(msg) {
                 page_boop::Action::None => Task::none(),
@@ -314,7 +347,7 @@ impl App {
             Slide::Constructors => self.constructors_slide.view(theme),
             Slide::Update => self.update_slide.view(theme),
             Slide::Tasks => self.tasks_slide.view(theme),
-            Slide::Subscriptions => self.subscriptions_slide.view(theme),
+            Slide::Subscriptions => self.subscriptions_slide.view(theme, &self.chaos),
             Slide::Interactive => self.interactive_slide.view(&self.page_boop),
             Slide::CommunityWidgets => self.community_widgets_slide.view(&self.terminal),
             Slide::Quiz => slides::quiz::QuizSlides::view_quiz_screen(&self.quiz),
@@ -360,12 +393,20 @@ impl App {
             nav_bar
         ];
 
-        if screen == Slide::Subscriptions {
-            let chaos_overlay = canvas(chaos::ChaosOverlay {
-                circles: self.chaos.circles(),
-            })
-            .width(iced::Fill)
-            .height(iced::Fill);
+        if self.chaos.effect() != chaos::Effect::None {
+            let chaos_overlay = Element::from(
+                canvas(chaos::ChaosOverlay {
+                    circles: self.chaos.circles(),
+                    particles: self.chaos.particles(),
+                    track_pointer: self.chaos.forces().attraction != 0.0,
+                    cache: self.chaos.cache(),
+                    logo: self.chaos.logo(),
+                    hud: self.chaos.hud(),
+                })
+                .width(iced::Fill)
+                .height(iced::Fill),
+            )
+            .map(Message::Chaos);
 
             container(stack![layout, chaos_overlay])
                 .width(iced::Fill)
@@ -1,3 +1,10 @@
+/// Options answering the validation question right, by index.
+pub const VALIDATION_CORRECT: &[u8] = &[2];
+/// Options answering the HTTP question right.
+pub const HTTP_CORRECT: &[u8] = &[2];
+/// Options answering the disabled button question right; both ways work.
+pub const BUTTON_CORRECT: &[u8] = &[0, 1];
+
 #[derive(Default)]
 pub struct Quiz {
     answer: Option<u8>,
@@ -14,17 +21,32 @@ pub enum Message {
 
 pub enum Action {
     None,
+    /// The answer just given is right.
+    Correct,
 }
 
 impl Quiz {
     #[must_use]
     pub fn update(&mut self, message: Message) -> Action {
-        match message {
-            Message::Answer(a) => self.answer = Some(a),
-            Message::HttpAnswer(a) => self.http_answer = Some(a),
-            Message::ButtonAnswer(a) => self.button_answer = Some(a),
+        let correct = match message {
+            Message::Answer(a) => {
+                self.answer = Some(a);
+                VALIDATION_CORRECT.contains(&a)
+            }
+            Message::HttpAnswer(a) => {
+                self.http_answer = Some(a);
+                HTTP_CORRECT.contains(&a)
+            }
+            Message::ButtonAnswer(a) => {
+                self.button_answer = Some(a);
+                BUTTON_CORRECT.contains(&a)
+            }
+        };
+        if correct {
+            Action::Correct
+        } else {
+            Action::None
         }
-        Action::None
     }
 
     pub fn answer(&self) -> Option<u8> {
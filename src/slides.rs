@@ -18,6 +18,8 @@ pub mod view;
 use rotalubat::Rotalubat;
 use strum::{Display, EnumCount};
 
+use crate::chaos::Effect;
+
 #[derive(Clone, Copy, PartialEq, Eq, Default, Display, Rotalubat, EnumCount)]
 #[rotalubat(mode = "clamp")]
 pub enum Slide {
@@ -108,4 +110,14 @@ impl Slide {
     pub fn is_last(&self) -> bool {
         *self == Slide::Recap
     }
+
+    /// The effect playing over this slide.
+    pub fn effect(&self) -> Effect {
+        match self {
+            Slide::Subscriptions => Effect::Chaos,
+            Slide::Quiz | Slide::QuizHttp | Slide::QuizButton => Effect::Confetti,
+            Slide::Recap => Effect::Snow,
+            _ => Effect::None,
+        }
+    }
 }
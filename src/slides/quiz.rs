@@ -85,19 +85,18 @@ impl QuizSlides {
         question: &'a str,
         options: &'a [(&'a str, Message)],
         answer: Option<u8>,
-        feedbacks: &'a [(u8, &'a str, bool)],
+        correct: &[u8],
+        feedbacks: &'a [(u8, &'a str)],
     ) -> Element<'a, Message> {
         assert_eq!(options.len(), 4, "WWM quiz requires exactly 4 options");
 
-        let correct_idx = feedbacks.iter().find(|(_, _, c)| *c).map(|(i, _, _)| *i);
-
         let buttons: Vec<Element<'a, Message>> = options
             .iter()
             .enumerate()
             .map(|(i, (label, msg))| {
                 let idx = i as u8;
                 let is_selected = answer == Some(idx);
-                let is_correct = correct_idx == Some(idx);
+                let is_correct = correct.contains(&idx);
                 let answered = answer.is_some();
 
                 let content = row![
@@ -130,13 +129,14 @@ impl QuizSlides {
                 .color(SUBTITLE_COLOR)
                 .into(),
             Some(idx) => {
-                if let Some((_, fb, is_correct)) = feedbacks.iter().find(|(i, _, _)| *i == idx) {
-                    let icon: Element<'_, Message> = if *is_correct {
+                if let Some((_, fb)) = feedbacks.iter().find(|(i, _)| *i == idx) {
+                    let is_correct = correct.contains(&idx);
+                    let icon: Element<'_, Message> = if is_correct {
                         icon_circle_check().size(24).color(CORRECT_COLOR).into()
                     } else {
                         icon_circle_x().size(24).color(INCORRECT_COLOR).into()
                     };
-                    let color = if *is_correct {
+                    let color = if is_correct {
                         CORRECT_COLOR
                     } else {
                         INCORRECT_COLOR
@@ -179,27 +179,21 @@ impl QuizSlides {
                 ("In the Model", Message::Quiz(quiz::Message::Answer(3))),
             ],
             quiz.answer(),
+            quiz::VALIDATION_CORRECT,
             &[
                 (
                     2,
                     "Correct! The Update function processes input and validates data before updating the Model.",
-                    true,
                 ),
                 (
                     0,
                     "Not quite. The View only renders UI from state — it shouldn't contain logic.",
-                    false,
                 ),
                 (
                     1,
                     "Not quite. Messages are just data describing what happened — they don't contain logic.",
-                    false,
-                ),
-                (
-                    3,
-                    "Not quite. The Model only holds state, not logic.",
-                    false,
                 ),
+                (3, "Not quite. The Model only holds state, not logic."),
             ],
         )
     }
@@ -220,26 +214,23 @@ impl QuizSlides {
                 ("In the Model", Message::Quiz(quiz::Message::HttpAnswer(3))),
             ],
             quiz.http_answer(),
+            quiz::HTTP_CORRECT,
             &[
                 (
                     2,
                     "Correct! HTTP requests are async operations, so they belong in a Task returned from Update.",
-                    true,
                 ),
                 (
                     0,
                     "Not quite. The View only renders UI — it can't perform side effects.",
-                    false,
                 ),
                 (
                     1,
                     "Not quite. Messages are just data — they describe events, not perform actions.",
-                    false,
                 ),
                 (
                     3,
                     "Not quite. The Model only holds state — it doesn't perform operations.",
-                    false,
                 ),
             ],
         )
@@ -267,26 +258,20 @@ impl QuizSlides {
                 ),
             ],
             quiz.button_answer(),
+            quiz::BUTTON_CORRECT,
             &[
                 (
                     0,
                     "Correct! The View can check the condition directly and conditionally call on_press.",
-                    true,
                 ),
                 (
                     1,
                     "Also correct! For complex logic, Update can set a flag that the View reads.",
-                    true,
-                ),
-                (
-                    2,
-                    "Not quite. Messages describe events, not UI commands.",
-                    false,
                 ),
+                (2, "Not quite. Messages describe events, not UI commands."),
                 (
                     3,
                     "Not quite. A separate flag is unnecessary — the View can derive disabled state from existing data.",
-                    false,
                 ),
             ],
         )
@@ -1,6 +1,8 @@
+use std::ops::RangeInclusive;
+
 use iced::{
     Element, Theme,
-    widget::{column, markdown, scrollable, space, text},
+    widget::{checkbox, column, markdown, row, scrollable, slider, space, text},
 };
 use iced_anim::widget::button;
 
@@ -32,7 +34,9 @@ impl Default for SubscriptionsSlide {
 }
 
 impl SubscriptionsSlide {
-    pub fn view(&self, theme: &Theme) -> Element<'_, Message> {
+    pub fn view(&self, theme: &Theme, chaos: &chaos::Chaos) -> Element<'_, Message> {
+        let forces = chaos.forces();
+
         scrollable(
             column![
                 text("Subscriptions feed external (asynchronous) events into your app.")
@@ -47,11 +51,86 @@ impl SubscriptionsSlide {
                 space().height(16.0),
                 text("Other common uses: timers, window events, WebSocket messages.")
                     .size(TEXT_SIZE),
+                text("Page Boop's WebSocket mode streams a connection through one.")
+                    .size(TEXT_SIZE - 4)
+                    .color(SUBTITLE_COLOR),
                 space().height(12.0),
                 button("🚨 Panic!").on_press(Message::Chaos(chaos::Message::PanicChaos)),
+                text("Grab, fling or click the circles: a canvas handles mouse events too.")
+                    .size(TEXT_SIZE - 4)
+                    .color(SUBTITLE_COLOR),
+                text("Each slider sends a message the next tick's physics picks up.")
+                    .size(TEXT_SIZE - 4)
+                    .color(SUBTITLE_COLOR),
+                labeled_slider(
+                    format!("Bounciness: {:.2}", chaos.restitution()),
+                    0.0..=1.0,
+                    0.05,
+                    chaos.restitution(),
+                    chaos::Message::RestitutionChanged,
+                ),
+                labeled_slider(
+                    format!("Gravity: {:.0} px/s²", forces.gravity),
+                    -1000.0..=1000.0,
+                    50.0,
+                    forces.gravity,
+                    chaos::Message::GravityChanged,
+                ),
+                labeled_slider(
+                    format!("Wind: {:.0} px/s²", forces.wind),
+                    -500.0..=500.0,
+                    25.0,
+                    forces.wind,
+                    chaos::Message::WindChanged,
+                ),
+                labeled_slider(
+                    format!("Drag: {:.2} /s", forces.drag),
+                    0.0..=2.0,
+                    0.05,
+                    forces.drag,
+                    chaos::Message::DragChanged,
+                ),
+                labeled_slider(
+                    format!("Cursor pull: {:.0} px/s²", forces.attraction),
+                    -2000.0..=2000.0,
+                    100.0,
+                    forces.attraction,
+                    chaos::Message::AttractionChanged,
+                ),
+                button("Reset forces").on_press(Message::Chaos(chaos::Message::ResetForces)),
+                checkbox(chaos.hud().is_some())
+                    .label("Show performance HUD")
+                    .on_toggle(|hud| Message::Chaos(chaos::Message::HudToggled(hud))),
+                checkbox(chaos.trails())
+                    .label("Motion trails")
+                    .on_toggle(|trails| Message::Chaos(chaos::Message::TrailsToggled(trails))),
+                checkbox(chaos.stress())
+                    .label(format!("Stress test ({} circles)", chaos::STRESS_CIRCLES))
+                    .on_toggle(|stress| Message::Chaos(chaos::Message::StressToggled(stress))),
             ]
             .spacing(8.0),
         )
         .into()
     }
 }
+
+fn labeled_slider<'a>(
+    label: String,
+    range: RangeInclusive<f32>,
+    step: f32,
+    value: f32,
+    on_change: fn(f32) -> chaos::Message,
+) -> Element<'a, Message> {
+    row![
+        text(label)
+            .size(TEXT_SIZE - 4)
+            .color(SUBTITLE_COLOR)
+            .width(260),
+        slider(range, value, move |value| Message::Chaos(on_change(value)))
+            .step(step)
+            .width(200),
+    ]
+    .spacing(12)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
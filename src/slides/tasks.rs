@@ -10,8 +10,9 @@ const MD_TASKS: &str = r#"
 fn update(&mut self, message: Message) -> Task<Message> {
     ...
     Message::Action => {
-        return Task::perform(
+        return Task::sip(
             fetch_url(self.url.clone(), self.secure, self.mode),
+            Message::Progress,
             Message::Result,
         );
     }
@@ -48,6 +49,11 @@ impl TasksSlide {
                     "When the async work completes, the result is usually wrapped in the message."
                 )
                 .size(TEXT_SIZE),
+                space().height(8.0),
+                text(
+                    "Task::sip also maps intermediate progress, so one Task can produce many Messages."
+                )
+                .size(TEXT_SIZE),
             ]
             .spacing(8.0),
         )
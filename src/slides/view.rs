@@ -1,11 +1,17 @@
 use iced::{
     Color, Element, Theme,
+    theme::Mode,
     widget::button as iced_button,
-    widget::{column, container, markdown, pick_list, row, scrollable, space, text},
+    widget::{
+        checkbox, column, container, markdown, pick_list, row, scrollable, space, text, text_input,
+    },
 };
 use iced_anim::widget::button;
 
-use crate::{Message, SUBTITLE_COLOR, TEXT_SIZE, render_markdown, theming};
+use crate::{
+    CORRECT_COLOR, FIRA_MONO, INCORRECT_COLOR, Message, SUBTITLE_COLOR, TEXT_SIZE, render_markdown,
+    theming,
+};
 
 const MD_VIEW: &str = r#"
 ```rust
@@ -122,6 +128,7 @@ impl ViewSlide {
                 ]
                 .spacing(12.0)
                 .align_y(iced::Alignment::Center),
+                Self::view_auto_theme(theming),
                 space().height(24.0),
                 text("Sample widgets with the current theme:")
                     .size(TEXT_SIZE - 4)
@@ -145,9 +152,107 @@ impl ViewSlide {
                         .style(iced_button::danger),
                 ]
                 .spacing(12.0),
+                space().height(24.0),
+                text("Or build your own from a Palette; every change applies right away:")
+                    .size(TEXT_SIZE - 4)
+                    .color(SUBTITLE_COLOR),
+                space().height(12.0),
+                Self::view_palette_editor(theming),
             ]
             .spacing(8.0),
         )
         .into()
     }
+
+    fn view_auto_theme(theming: &theming::Theming) -> Element<'_, Message> {
+        let mode = match theming.system_mode() {
+            Mode::Light => "light",
+            Mode::Dark => "dark",
+            Mode::None => "no preference",
+        };
+        let pair = row![
+            text("Light:").size(TEXT_SIZE - 4),
+            pick_list(Theme::ALL, Some(theming.light_theme()), |t| {
+                Message::Theming(theming::Message::LightThemeChanged(t))
+            }),
+            text("Dark:").size(TEXT_SIZE - 4),
+            pick_list(Theme::ALL, Some(theming.dark_theme()), |t| {
+                Message::Theming(theming::Message::DarkThemeChanged(t))
+            }),
+        ]
+        .spacing(12.0)
+        .align_y(iced::Alignment::Center);
+
+        column![
+            checkbox(theming.is_auto())
+                .label(format!("Auto: follow the system ({mode})"))
+                .on_toggle(|auto| Message::Theming(theming::Message::AutoToggled(auto))),
+            pair,
+        ]
+        .spacing(8.0)
+        .into()
+    }
+
+    fn view_palette_editor(theming: &theming::Theming) -> Element<'_, Message> {
+        let swatches = theming::Role::ALL.into_iter().map(|role| {
+            let color = role.get(theming.palette());
+            let swatch =
+                button(
+                    container(space().width(60).height(40)).style(move |_| container::Style {
+                        background: Some(color.into()),
+                        ..Default::default()
+                    }),
+                )
+                .on_press(Message::Theming(theming::Message::EditColor(role)));
+
+            let picker = iced_aw::helpers::color_picker(
+                theming.editing() == Some(role),
+                color,
+                swatch,
+                Message::Theming(theming::Message::CancelEdit),
+                move |c| Message::Theming(theming::Message::ColorEdited(role, c)),
+            );
+            column![
+                text(role.to_string())
+                    .size(TEXT_SIZE - 6)
+                    .color(SUBTITLE_COLOR),
+                picker,
+            ]
+            .spacing(4.0)
+            .into()
+        });
+
+        let status: Element<'_, Message> = match theming.palette_status() {
+            Some(Ok(status)) => text(status).size(TEXT_SIZE - 6).color(CORRECT_COLOR).into(),
+            Some(Err(error)) => text(error)
+                .size(TEXT_SIZE - 6)
+                .color(INCORRECT_COLOR)
+                .into(),
+            None => space().into(),
+        };
+
+        column![
+            row(swatches).spacing(12.0),
+            row![
+                button(text("Start from current theme"))
+                    .on_press(Message::Theming(theming::Message::CopyThemePalette)),
+                text_input(theming::DEFAULT_PALETTE_PATH, theming.palette_path())
+                    .on_input(|path| Message::Theming(theming::Message::PalettePathChanged(path)))
+                    .font(FIRA_MONO)
+                    .width(280),
+                button(text("Save")).on_press(Message::Theming(theming::Message::SavePalette)),
+                button(text("Load")).on_press(Message::Theming(theming::Message::LoadPalette)),
+                button(text("Copy Rust snippet"))
+                    .on_press(Message::Theming(theming::Message::CopySnippet)),
+            ]
+            .spacing(12.0)
+            .align_y(iced::Alignment::Center),
+            status,
+            container(text(theming.snippet()).font(FIRA_MONO).size(TEXT_SIZE - 8))
+                .padding(12.0)
+                .style(container::rounded_box),
+        ]
+        .spacing(12.0)
+        .into()
+    }
 }
@@ -28,11 +28,6 @@ impl theme::Base for AppTheme {
     }
 }
 
-// Gruvbox dark colors for code blocks (high contrast)
-const CODE_BG: Color = Color::from_rgba(0.18, 0.18, 0.18, 0.8);
-const CODE_BORDER: Color = Color::from_rgb(0.30, 0.30, 0.28);
-const CODE_TEXT: Color = Color::from_rgb(0.92, 0.86, 0.70);
-
 impl container::Catalog for AppTheme {
     type Class<'a> = <Theme as container::Catalog>::Class<'a>;
 
@@ -105,17 +100,38 @@ impl table::Catalog for AppTheme {
     }
 }
 
+/// Background of the Base16 Ocean highlighting scheme.
+const OCEAN_BACKGROUND: Color = Color::from_rgb8(0x2B, 0x30, 0x3B);
+/// Plain text color of the Base16 Ocean highlighting scheme.
+const OCEAN_TEXT: Color = Color::from_rgb8(0xC0, 0xC5, 0xCE);
+
 impl markdown::Catalog for AppTheme {
     fn code_block<'a>() -> <Self as container::Catalog>::Class<'a> {
-        Box::new(|_theme| container::Style {
-            background: Some(CODE_BG.into()),
-            border: Border {
-                color: CODE_BORDER,
-                width: 1.0,
-                radius: 4.0.into(),
-            },
-            text_color: Some(CODE_TEXT),
-            ..Default::default()
+        // The markdown widget always highlights with Base16 Ocean, a dark
+        // scheme, so code stays on a dark background even in light themes.
+        Box::new(|theme: &Theme| {
+            let palette = theme.extended_palette();
+            let (background, text) = if palette.is_dark {
+                (
+                    Color {
+                        a: 0.8,
+                        ..palette.background.weak.color
+                    },
+                    palette.background.weak.text,
+                )
+            } else {
+                (OCEAN_BACKGROUND, OCEAN_TEXT)
+            };
+            container::Style {
+                background: Some(background.into()),
+                border: Border {
+                    color: palette.background.strong.color,
+                    width: 1.0,
+                    radius: 4.0.into(),
+                },
+                text_color: Some(text),
+                ..Default::default()
+            }
         })
     }
 }
@@ -1,9 +1,103 @@
-use iced::{Color, Theme};
+use std::fmt;
+use std::path::PathBuf;
+
+use iced::{
+    Color, Task, Theme,
+    theme::{Mode, Palette},
+};
+
+/// Where the palette editor saves to and loads from by default.
+pub const DEFAULT_PALETTE_PATH: &str = "custom-theme.txt";
+
+/// Name of the theme built from the edited palette.
+const CUSTOM_THEME_NAME: &str = "Custom";
 
 pub struct Theming {
     hover_color: Color,
     show_color_picker: bool,
+    /// The theme in use; with `auto` on, whichever of the pair suits the system.
     theme: Theme,
+    /// Follow the system's light or dark preference.
+    auto: bool,
+    light_theme: Theme,
+    dark_theme: Theme,
+    system_mode: Mode,
+    /// The palette being edited, applied as a custom theme on every change.
+    palette: Palette,
+    /// Palette color whose picker is open.
+    editing: Option<Role>,
+    palette_path: String,
+    palette_status: Option<Result<String, String>>,
+}
+
+/// One color of a [`Palette`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Background,
+    Text,
+    Primary,
+    Success,
+    Warning,
+    Danger,
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [
+        Role::Background,
+        Role::Text,
+        Role::Primary,
+        Role::Success,
+        Role::Warning,
+        Role::Danger,
+    ];
+
+    pub fn get(self, palette: &Palette) -> Color {
+        match self {
+            Role::Background => palette.background,
+            Role::Text => palette.text,
+            Role::Primary => palette.primary,
+            Role::Success => palette.success,
+            Role::Warning => palette.warning,
+            Role::Danger => palette.danger,
+        }
+    }
+
+    fn get_mut(self, palette: &mut Palette) -> &mut Color {
+        match self {
+            Role::Background => &mut palette.background,
+            Role::Text => &mut palette.text,
+            Role::Primary => &mut palette.primary,
+            Role::Success => &mut palette.success,
+            Role::Warning => &mut palette.warning,
+            Role::Danger => &mut palette.danger,
+        }
+    }
+
+    /// The `Palette` field name, as used in saved files and snippets.
+    fn field(self) -> &'static str {
+        match self {
+            Role::Background => "background",
+            Role::Text => "text",
+            Role::Primary => "primary",
+            Role::Success => "success",
+            Role::Warning => "warning",
+            Role::Danger => "danger",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Role::Background => "Background",
+            Role::Text => "Text",
+            Role::Primary => "Primary",
+            Role::Success => "Success",
+            Role::Warning => "Warning",
+            Role::Danger => "Danger",
+        };
+        f.write_str(name)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -12,19 +106,44 @@ pub enum Message {
     SubmitColor(Color),
     CancelColorPicker,
     ThemeChanged(Theme),
+    AutoToggled(bool),
+    LightThemeChanged(Theme),
+    DarkThemeChanged(Theme),
+    SystemModeChanged(Mode),
+    EditColor(Role),
+    ColorEdited(Role, Color),
+    CancelEdit,
+    /// Start editing from the palette of the current theme.
+    CopyThemePalette,
+    PalettePathChanged(String),
+    SavePalette,
+    PaletteSaved(Result<PathBuf, String>),
+    LoadPalette,
+    PaletteLoaded(Result<Palette, String>),
+    CopySnippet,
 }
 
 pub enum Action {
     None,
     ThemeChanged,
+    Run(Task<Message>),
 }
 
 impl Default for Theming {
     fn default() -> Self {
+        let theme = Theme::GruvboxLight;
         Self {
             hover_color: Color::from_rgb(0.3, 0.7, 1.0),
             show_color_picker: false,
-            theme: Theme::GruvboxLight,
+            palette: theme.palette(),
+            light_theme: theme.clone(),
+            dark_theme: Theme::GruvboxDark,
+            theme,
+            auto: true,
+            system_mode: Mode::None,
+            editing: None,
+            palette_path: DEFAULT_PALETTE_PATH.to_string(),
+            palette_status: None,
         }
     }
 }
@@ -47,12 +166,99 @@ impl Theming {
                 Action::None
             }
             Message::ThemeChanged(theme) => {
+                self.auto = false;
                 self.theme = theme;
                 Action::ThemeChanged
             }
+            Message::AutoToggled(auto) => {
+                self.auto = auto;
+                self.follow_system()
+            }
+            Message::LightThemeChanged(theme) => {
+                self.light_theme = theme;
+                self.follow_system()
+            }
+            Message::DarkThemeChanged(theme) => {
+                self.dark_theme = theme;
+                self.follow_system()
+            }
+            Message::SystemModeChanged(mode) => {
+                self.system_mode = mode;
+                self.follow_system()
+            }
+            Message::EditColor(role) => {
+                self.editing = Some(role);
+                Action::None
+            }
+            Message::ColorEdited(role, color) => {
+                *role.get_mut(&mut self.palette) = color;
+                self.editing = None;
+                self.apply_palette()
+            }
+            Message::CancelEdit => {
+                self.editing = None;
+                Action::None
+            }
+            Message::CopyThemePalette => {
+                self.palette = self.theme.palette();
+                Action::None
+            }
+            Message::PalettePathChanged(path) => {
+                self.palette_path = path;
+                Action::None
+            }
+            Message::SavePalette => Action::Run(Task::perform(
+                save_palette(self.palette_path.clone().into(), to_text(&self.palette)),
+                Message::PaletteSaved,
+            )),
+            Message::PaletteSaved(result) => {
+                self.palette_status =
+                    Some(result.map(|path| format!("Saved to {}", path.display())));
+                Action::None
+            }
+            Message::LoadPalette => Action::Run(Task::perform(
+                load_palette(self.palette_path.clone().into(), self.palette),
+                Message::PaletteLoaded,
+            )),
+            Message::PaletteLoaded(Ok(palette)) => {
+                self.palette = palette;
+                self.palette_status = Some(Ok(format!("Loaded {}", self.palette_path)));
+                self.apply_palette()
+            }
+            Message::PaletteLoaded(Err(error)) => {
+                self.palette_status = Some(Err(error));
+                Action::None
+            }
+            Message::CopySnippet => {
+                self.palette_status = Some(Ok("Copied the snippet".to_string()));
+                Action::Run(iced::clipboard::write(to_snippet(&self.palette)))
+            }
         }
     }
 
+    fn apply_palette(&mut self) -> Action {
+        self.auto = false;
+        self.theme = Theme::custom(CUSTOM_THEME_NAME, self.palette);
+        Action::ThemeChanged
+    }
+
+    /// Switch to the theme of the pair matching the system, if following it.
+    fn follow_system(&mut self) -> Action {
+        if !self.auto {
+            return Action::None;
+        }
+        let theme = match self.system_mode {
+            Mode::Dark => &self.dark_theme,
+            // Without a preference, stay light like the slides were designed.
+            Mode::Light | Mode::None => &self.light_theme,
+        };
+        if *theme == self.theme {
+            return Action::None;
+        }
+        self.theme = theme.clone();
+        Action::ThemeChanged
+    }
+
     pub fn hover_color(&self) -> Color {
         self.hover_color
     }
@@ -64,4 +270,128 @@ impl Theming {
     pub fn theme(&self) -> &Theme {
         &self.theme
     }
+
+    pub fn is_auto(&self) -> bool {
+        self.auto
+    }
+
+    pub fn light_theme(&self) -> &Theme {
+        &self.light_theme
+    }
+
+    pub fn dark_theme(&self) -> &Theme {
+        &self.dark_theme
+    }
+
+    pub fn system_mode(&self) -> Mode {
+        self.system_mode
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn editing(&self) -> Option<Role> {
+        self.editing
+    }
+
+    pub fn palette_path(&self) -> &str {
+        &self.palette_path
+    }
+
+    pub fn palette_status(&self) -> Option<&Result<String, String>> {
+        self.palette_status.as_ref()
+    }
+
+    /// The edited palette as Rust code to paste into an app.
+    pub fn snippet(&self) -> String {
+        to_snippet(&self.palette)
+    }
+}
+
+/// One `field = #rrggbb` line per color.
+fn to_text(palette: &Palette) -> String {
+    Role::ALL
+        .iter()
+        .map(|role| format!("{} = {}\n", role.field(), role.get(palette)))
+        .collect()
+}
+
+/// Read colors written by [`to_text`] over `base`; missing ones keep its colors.
+fn from_text(text: &str, mut base: Palette) -> Result<Palette, String> {
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || format!("Line {}: expected `name = #rrggbb`", number + 1);
+        let (field, value) = line.split_once('=').ok_or_else(invalid)?;
+        let role = Role::ALL
+            .into_iter()
+            .find(|role| role.field() == field.trim())
+            .ok_or_else(|| format!("Line {}: unknown color `{}`", number + 1, field.trim()))?;
+        *role.get_mut(&mut base) = parse_hex(value.trim()).ok_or_else(invalid)?;
+    }
+    Ok(base)
+}
+
+/// Parse `#rrggbb` or `#rrggbbaa`.
+fn parse_hex(hex: &str) -> Option<Color> {
+    let digits = hex.strip_prefix('#')?;
+    if !matches!(digits.len(), 6 | 8) || !digits.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    let alpha = if digits.len() == 8 { channel(6)? } else { 255 };
+    Some(Color::from_rgba8(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        f32::from(alpha) / 255.0,
+    ))
+}
+
+/// The palette as a `const`, keeping the alpha of translucent colors.
+fn to_snippet(palette: &Palette) -> String {
+    let fields: String = Role::ALL
+        .iter()
+        .map(|role| {
+            let color = role.get(palette);
+            let [r, g, b, a] = color.into_rgba8();
+            let constructor = if color.a < 1.0 {
+                format!(
+                    "from_rgba8(0x{r:02X}, 0x{g:02X}, 0x{b:02X}, {:.3})",
+                    f32::from(a) / 255.0
+                )
+            } else {
+                format!("from_rgb8(0x{r:02X}, 0x{g:02X}, 0x{b:02X})")
+            };
+            format!("    {}: Color::{constructor},\n", role.field())
+        })
+        .collect();
+    format!(
+        "use iced::{{Color, Theme, theme::Palette}};\n\
+         \n\
+         const PALETTE: Palette = Palette {{\n\
+         {fields}\
+         }};\n\
+         \n\
+         fn theme(&self) -> Theme {{\n    \
+             Theme::custom(\"{CUSTOM_THEME_NAME}\", PALETTE)\n\
+         }}\n"
+    )
+}
+
+async fn save_palette(path: PathBuf, text: String) -> Result<PathBuf, String> {
+    tokio::fs::write(&path, text)
+        .await
+        .map_err(|e| format!("Error saving {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+async fn load_palette(path: PathBuf, base: Palette) -> Result<Palette, String> {
+    let text = tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Error loading {}: {e}", path.display()))?;
+    from_text(&text, base)
 }